@@ -1,9 +1,19 @@
 //! Core state information.
 
+use std::fmt::{self, Display};
+use std::ops::Range;
+
 #[cfg(feature = "serde")]
 use serde_with::{As, Bytes};
 
-use super::{Data, Ident};
+use super::{Data, Ident, Typed};
+use crate::{Error, Result};
+
+mod io;
+mod model;
+
+pub use self::io::{Io, IoMut, Key0, Key1, Lcdc, Nr52, Reg, Stat, Tac};
+pub use self::model::{Device, Family, Model, Revision};
 
 /// `CORE` block.
 ///
@@ -26,15 +36,24 @@ pub struct Core {
 }
 
 impl Core {
-    /// Identifier for this block.
-    const IDENT: Ident = Ident::new(*b"CORE");
     /// Constant length of this block.
     const LEN: u32 = 0xd0;
+
+    /// Constructs a new `Core` of the [current](Version::CURRENT) version.
+    #[must_use]
+    pub fn new(model: Model, reg: Registers, mem: Locations) -> Self {
+        Self {
+            version: Version::CURRENT,
+            model,
+            reg,
+            mem,
+        }
+    }
 }
 
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Data for Core {
-    fn ident() -> Ident {
+    fn ident(&self) -> Ident {
         Self::IDENT
     }
 
@@ -43,12 +62,16 @@ impl Data for Core {
     }
 }
 
+impl Typed for Core {
+    const IDENT: Ident = Ident::new(*b"CORE");
+}
+
 /// BESS version.
 ///
 /// Both major and minor versions should be 1. Implementations are expected to
 /// reject incompatible majors, but still attempt to read newer minor versions.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Version {
     /// Major BESS version as a 16-bit integer.
     pub major: u16,
@@ -56,40 +79,38 @@ pub struct Version {
     pub minor: u16,
 }
 
-/// Model identifier.
-///
-/// Four-character string to identify Game Boy models.
-///
-/// 1. The first letter represents mutually-incompatible families of models and
-///    is required. The allowed values are `'G'` for the original Game Boy
-///    family, `'S'` for the Super Game Boy family, and `'C'` for the Game Boy
-///    Color and Advance family.
-/// 2. The second letter represents a specific model within the family, and is
-///    optional (If an implementation does not distinguish between specific
-///    models in a family, a space character may be used). The allowed values
-///    for family G are `'D'` for DMG and `'M'` for MGB; the allowed values for
-///    family S are `'N'` for NTSC, `'P'` for PAL, and `'2'` for SGB2; and the allowed
-///    values for family C are `'C'` for CGB, and `'A'` for the various GBA line
-///    models.
-/// 3. The third letter represents a specific CPU revision within a model, and
-///    is optional (If an implementation does not distinguish between revisions,
-///    a space character may be used). The allowed values for model GD (DMG) are
-///    `'0'` and `'A'`, through `'C'`; the allowed values for model CC (CGB) are `'0'`
-///    and `'A'`, through `'E'`; the allowed values for model CA (AGB, AGS, GBP) are
-///    `'0'`, `'A'` and `'B'`; and for every other model this value must be a space
-///    character.
-/// 4. The last character is used for padding and must be a space character.
-///
-/// # Examples
-///
-/// |  Model   | Description
-/// |----------|-------------
-/// | `"GD  "` | A DMG of an unspecified revision.
-/// | `"CCE "` | A CGB using CPU revision E.
-/// | `"S   "` | Some model of the SGB family.
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug)]
-pub struct Model(pub [u8; 4]);
+impl Version {
+    /// Version implemented by this crate.
+    pub const CURRENT: Self = Self { major: 1, minor: 1 };
+
+    /// Checks that this version is compatible with [`Version::CURRENT`].
+    ///
+    /// Newer minor versions are accepted, as they are expected to remain
+    /// readable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the major version differs.
+    pub fn check(&self) -> Result<()> {
+        if self.major == Self::CURRENT.major {
+            Ok(())
+        } else {
+            Err(Error::Version(*self))
+        }
+    }
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Self::CURRENT
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
 
 /// Register values.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -112,15 +133,31 @@ pub struct Registers {
     /// The value of the IE register.
     pub ie: u8,
     /// Execution state (0 = running; 1 = halted; 2 = stopped).
+    ///
+    /// Followed by a reserved byte, which must be 0.
+    #[cfg_attr(feature = "serde", serde(with = "reserved"))]
     pub exe: Execution,
     /// The values of every memory-mapped register (128 bytes).
     #[cfg_attr(feature = "serde", serde(with = "As::<Bytes>"))]
     pub mmio: Mmio,
 }
 
+impl Registers {
+    /// Gets a named view over the memory-mapped registers.
+    #[must_use]
+    pub fn io(&self) -> Io<'_> {
+        Io::new(&self.mmio)
+    }
+
+    /// Gets a mutable named view over the memory-mapped registers.
+    pub fn io_mut(&mut self) -> IoMut<'_> {
+        IoMut::new(&mut self.mmio)
+    }
+}
+
 /// Execution state.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 #[rustfmt::skip]
 pub enum Execution {
     Running = 0,
@@ -128,6 +165,36 @@ pub enum Execution {
     Stopped = 2,
 }
 
+/// Serialization of fields followed by a reserved byte.
+#[cfg(feature = "serde")]
+mod reserved {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        (value, 0u8).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Reserved::deserialize(deserializer).map(|Reserved(value, _)| value)
+    }
+
+    /// Value followed by a reserved byte.
+    ///
+    /// Named as [`TRANSPARENT`](crate::serde::de::TRANSPARENT), so that errors
+    /// are reported by the field rather than by index.
+    #[derive(Deserialize)]
+    #[serde(rename = "$bess::Transparent")]
+    struct Reserved<T>(T, #[allow(dead_code)] u8);
+}
+
 /// Memory-mapped registers.
 ///
 /// The values of memory-mapped registers should be written 'as-is' to memory as
@@ -191,12 +258,156 @@ pub struct Locations {
     pub obj: Pointer,
 }
 
+impl Locations {
+    /// Checks that every pointer lies within the context buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any pointer is out of bounds.
+    pub fn check(&self, ctx: &[u8]) -> Result<()> {
+        self.check_len(ctx.len())
+    }
+
+    /// Checks that every pointer lies within a context of `len` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any pointer is out of bounds.
+    pub fn check_len(&self, len: usize) -> Result<()> {
+        Region::ALL
+            .into_iter()
+            .try_for_each(|region| self.get(region).range(len).map(|_| ()))
+    }
+
+    /// Gets the pointer to a region's buffer.
+    #[must_use]
+    pub fn get(&self, region: Region) -> Pointer {
+        match region {
+            Region::Wram => self.wram,
+            Region::Vram => self.vram,
+            Region::Eram => self.eram,
+            Region::Oam => self.oam,
+            Region::Hram => self.hram,
+            Region::Bgp => self.bgp,
+            Region::Obj => self.obj,
+        }
+    }
+
+    /// Gets a mutable reference to the pointer to a region's buffer.
+    pub fn get_mut(&mut self, region: Region) -> &mut Pointer {
+        match region {
+            Region::Wram => &mut self.wram,
+            Region::Vram => &mut self.vram,
+            Region::Eram => &mut self.eram,
+            Region::Oam => &mut self.oam,
+            Region::Hram => &mut self.hram,
+            Region::Bgp => &mut self.bgp,
+            Region::Obj => &mut self.obj,
+        }
+    }
+}
+
+/// Memory regions stored as large buffers.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Region {
+    /// Work RAM.
+    Wram,
+    /// Video RAM.
+    Vram,
+    /// External (cartridge) RAM.
+    Eram,
+    /// Object attribute memory.
+    Oam,
+    /// High RAM.
+    Hram,
+    /// Background palettes.
+    Bgp,
+    /// Object palettes.
+    Obj,
+}
+
+impl Region {
+    /// Every region, in the order stored within [`Locations`].
+    pub const ALL: [Self; 7] = [
+        Self::Wram,
+        Self::Vram,
+        Self::Eram,
+        Self::Oam,
+        Self::Hram,
+        Self::Bgp,
+        Self::Obj,
+    ];
+
+    /// Gets the name of the region's field within [`Locations`].
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Wram => "wram",
+            Self::Vram => "vram",
+            Self::Eram => "eram",
+            Self::Oam => "oam",
+            Self::Hram => "hram",
+            Self::Bgp => "bgp",
+            Self::Obj => "obj",
+        }
+    }
+
+    /// Gets the expected size of the region's buffer on a family of models.
+    ///
+    /// Returns `None` for external RAM, whose size depends on the cartridge.
+    #[must_use]
+    pub fn size(self, family: Family) -> Option<u32> {
+        let cgb = family == Family::Cgb;
+        match self {
+            Self::Wram => Some(if cgb { 0x8000 } else { 0x2000 }),
+            Self::Vram => Some(if cgb { 0x4000 } else { 0x2000 }),
+            Self::Eram => None,
+            Self::Oam => Some(0xa0),
+            Self::Hram => Some(0x7f),
+            Self::Bgp | Self::Obj => Some(if cgb { 0x40 } else { 0 }),
+        }
+    }
+
+    /// Gets the offset of the region's pointer within the `CORE` block.
+    pub(crate) const fn offset(self) -> usize {
+        0x98 + 8 * self as usize
+    }
+}
+
 /// Wide-pointers to buffers.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct Pointer {
     /// Size of the buffer.
     pub len: u32,
     /// Absolute offset.
     pub ptr: u32,
 }
+
+impl Pointer {
+    /// Gets the buffer pointed to within `buf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pointer is out of bounds.
+    pub fn get<'a>(&self, buf: &'a [u8]) -> Result<&'a [u8]> {
+        self.range(buf.len()).map(|range| &buf[range])
+    }
+
+    /// Gets the range of the buffer pointed to within `len` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pointer is out of bounds.
+    pub fn range(&self, len: usize) -> Result<Range<usize>> {
+        let start = self.ptr as usize;
+        start
+            .checked_add(self.len as usize)
+            .filter(|&end| end <= len)
+            .map(|end| start..end)
+            .ok_or(Error::Bounds {
+                len: self.len,
+                ptr: self.ptr,
+            })
+    }
+}
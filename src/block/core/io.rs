@@ -0,0 +1,565 @@
+//! Named views over memory-mapped registers.
+
+use std::mem;
+use std::ops::{Index, IndexMut};
+
+use super::{Device, Family, Mmio, Model};
+
+/// Bus address of the first memory-mapped register.
+const BASE: u16 = 0xff00;
+
+/// Generates named register accessors.
+macro_rules! accessors {
+    ($($(#[$attr:meta])* $fn:ident => $reg:ident: $ty:ty,)*) => {$(
+        $(#[$attr])*
+        #[must_use]
+        pub fn $fn(&self) -> $ty {
+            <$ty>::from(self.reg(Reg::$reg))
+        }
+    )*};
+}
+
+/// Generates a bitfield register wrapper.
+macro_rules! bitfield {
+    ($(#[$attr:meta])* $name:ident { $($(#[$doc:meta])* $fn:ident = $bit:literal,)* }) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+        pub struct $name(pub u8);
+
+        impl $name {
+            $(
+                $(#[$doc])*
+                #[must_use]
+                pub fn $fn(self) -> bool {
+                    self.0 & (1 << $bit) != 0
+                }
+            )*
+        }
+
+        impl From<u8> for $name {
+            fn from(value: u8) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+    };
+}
+
+/// Read-only view over memory-mapped registers.
+///
+/// Registers are indexed by their bus address, from `0xff00` through `0xff7f`.
+#[derive(Clone, Copy, Debug)]
+pub struct Io<'a>(&'a Mmio);
+
+impl<'a> Io<'a> {
+    /// Constructs a new `Io` view.
+    #[must_use]
+    pub fn new(mmio: &'a Mmio) -> Self {
+        Self(mmio)
+    }
+
+    /// Gets the value of the register at a bus address.
+    ///
+    /// Returns `None` if the address is not of a memory-mapped register.
+    #[must_use]
+    pub fn get(&self, addr: u16) -> Option<u8> {
+        index(addr).map(|idx| self.0[idx])
+    }
+
+    /// Gets the value of a register.
+    #[must_use]
+    pub fn reg(&self, reg: Reg) -> u8 {
+        self[reg.addr()]
+    }
+
+    /// Gets the contents of wave pattern RAM (`0xff30`-`0xff3f`).
+    #[must_use]
+    pub fn wave(&self) -> &'a [u8] {
+        &self.0[0x30..0x40]
+    }
+
+    accessors! {
+        /// Joypad.
+        p1 => P1: u8,
+        /// Serial transfer data.
+        sb => Sb: u8,
+        /// Serial transfer control.
+        sc => Sc: u8,
+        /// Divider register.
+        div => Div: u8,
+        /// Timer counter.
+        tima => Tima: u8,
+        /// Timer modulo.
+        tma => Tma: u8,
+        /// Timer control.
+        tac => Tac: Tac,
+        /// Interrupt flag.
+        if_ => If: u8,
+        /// Channel 1 sweep.
+        nr10 => Nr10: u8,
+        /// Channel 1 length timer and duty cycle.
+        nr11 => Nr11: u8,
+        /// Channel 1 volume and envelope.
+        nr12 => Nr12: u8,
+        /// Channel 1 period (low).
+        nr13 => Nr13: u8,
+        /// Channel 1 period (high) and control.
+        nr14 => Nr14: u8,
+        /// Channel 2 length timer and duty cycle.
+        nr21 => Nr21: u8,
+        /// Channel 2 volume and envelope.
+        nr22 => Nr22: u8,
+        /// Channel 2 period (low).
+        nr23 => Nr23: u8,
+        /// Channel 2 period (high) and control.
+        nr24 => Nr24: u8,
+        /// Channel 3 DAC enable.
+        nr30 => Nr30: u8,
+        /// Channel 3 length timer.
+        nr31 => Nr31: u8,
+        /// Channel 3 output level.
+        nr32 => Nr32: u8,
+        /// Channel 3 period (low).
+        nr33 => Nr33: u8,
+        /// Channel 3 period (high) and control.
+        nr34 => Nr34: u8,
+        /// Channel 4 length timer.
+        nr41 => Nr41: u8,
+        /// Channel 4 volume and envelope.
+        nr42 => Nr42: u8,
+        /// Channel 4 frequency and randomness.
+        nr43 => Nr43: u8,
+        /// Channel 4 control.
+        nr44 => Nr44: u8,
+        /// Master volume and VIN panning.
+        nr50 => Nr50: u8,
+        /// Sound panning.
+        nr51 => Nr51: u8,
+        /// Sound on/off.
+        nr52 => Nr52: Nr52,
+        /// LCD control.
+        lcdc => Lcdc: Lcdc,
+        /// LCD status.
+        stat => Stat: Stat,
+        /// Background viewport Y position.
+        scy => Scy: u8,
+        /// Background viewport X position.
+        scx => Scx: u8,
+        /// LCD Y coordinate.
+        ly => Ly: u8,
+        /// LY compare.
+        lyc => Lyc: u8,
+        /// OAM DMA source address and start.
+        dma => Dma: u8,
+        /// Background palette data (non-CGB mode).
+        bgp => Bgp: u8,
+        /// Object palette 0 data (non-CGB mode).
+        obp0 => Obp0: u8,
+        /// Object palette 1 data (non-CGB mode).
+        obp1 => Obp1: u8,
+        /// Window Y position.
+        wy => Wy: u8,
+        /// Window X position plus 7.
+        wx => Wx: u8,
+        /// CPU mode select.
+        key0 => Key0: Key0,
+        /// Prepare speed switch.
+        key1 => Key1: Key1,
+        /// VRAM bank.
+        vbk => Vbk: u8,
+        /// Boot ROM mapping control.
+        bank => Bank: u8,
+        /// VRAM DMA source (high).
+        hdma1 => Hdma1: u8,
+        /// VRAM DMA source (low).
+        hdma2 => Hdma2: u8,
+        /// VRAM DMA destination (high).
+        hdma3 => Hdma3: u8,
+        /// VRAM DMA destination (low).
+        hdma4 => Hdma4: u8,
+        /// VRAM DMA length, mode and start.
+        hdma5 => Hdma5: u8,
+        /// Infrared communications port.
+        rp => Rp: u8,
+        /// Background color palette specification.
+        bcps => Bcps: u8,
+        /// Background color palette data.
+        bcpd => Bcpd: u8,
+        /// Object color palette specification.
+        ocps => Ocps: u8,
+        /// Object color palette data.
+        ocpd => Ocpd: u8,
+        /// Object priority mode.
+        opri => Opri: u8,
+        /// WRAM bank.
+        svbk => Svbk: u8,
+        /// Audio digital outputs 1 and 2.
+        pcm12 => Pcm12: u8,
+        /// Audio digital outputs 3 and 4.
+        pcm34 => Pcm34: u8,
+    }
+
+    /// Gets the selected VRAM bank.
+    #[must_use]
+    pub fn vram_bank(&self) -> u8 {
+        self.vbk() & 0b1
+    }
+
+    /// Gets the selected WRAM bank.
+    ///
+    /// A value of 0 selects bank 1.
+    #[must_use]
+    pub fn wram_bank(&self) -> u8 {
+        (self.svbk() & 0b111).max(1)
+    }
+
+    /// Checks if the boot ROM is still mapped.
+    #[must_use]
+    pub fn boot_mapped(&self) -> bool {
+        self.bank() == 0
+    }
+}
+
+impl Index<u16> for Io<'_> {
+    type Output = u8;
+
+    /// Indexes the register at a bus address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address is not of a memory-mapped register.
+    fn index(&self, addr: u16) -> &Self::Output {
+        &self.0[index(addr).expect("address out of range")]
+    }
+}
+
+/// Mutable view over memory-mapped registers.
+///
+/// Registers are indexed by their bus address, from `0xff00` through `0xff7f`.
+#[derive(Debug)]
+pub struct IoMut<'a>(&'a mut Mmio);
+
+impl<'a> IoMut<'a> {
+    /// Constructs a new `IoMut` view.
+    #[must_use]
+    pub fn new(mmio: &'a mut Mmio) -> Self {
+        Self(mmio)
+    }
+
+    /// Reborrows as a read-only view.
+    #[must_use]
+    pub fn as_io(&self) -> Io<'_> {
+        Io(self.0)
+    }
+
+    /// Gets a mutable reference to the register at a bus address.
+    ///
+    /// Returns `None` if the address is not of a memory-mapped register.
+    pub fn get_mut(&mut self, addr: u16) -> Option<&mut u8> {
+        index(addr).map(|idx| &mut self.0[idx])
+    }
+
+    /// Sets the value of a register.
+    pub fn set(&mut self, reg: Reg, value: impl Into<u8>) {
+        self[reg.addr()] = value.into();
+    }
+}
+
+impl Index<u16> for IoMut<'_> {
+    type Output = u8;
+
+    /// Indexes the register at a bus address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address is not of a memory-mapped register.
+    fn index(&self, addr: u16) -> &Self::Output {
+        &self.0[index(addr).expect("address out of range")]
+    }
+}
+
+impl IndexMut<u16> for IoMut<'_> {
+    /// Mutably indexes the register at a bus address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address is not of a memory-mapped register.
+    fn index_mut(&mut self, addr: u16) -> &mut Self::Output {
+        &mut self.0[index(addr).expect("address out of range")]
+    }
+}
+
+/// Converts a bus address into an index of [`Mmio`].
+fn index(addr: u16) -> Option<usize> {
+    addr.checked_sub(BASE)
+        .map(usize::from)
+        .filter(|&idx| idx < mem::size_of::<Mmio>())
+}
+
+/// Documented memory-mapped registers.
+///
+/// Each register's discriminant is the low byte of its bus address.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u8)]
+#[rustfmt::skip]
+pub enum Reg {
+    P1    = 0x00,
+    Sb    = 0x01,
+    Sc    = 0x02,
+    Div   = 0x04,
+    Tima  = 0x05,
+    Tma   = 0x06,
+    Tac   = 0x07,
+    If    = 0x0f,
+    Nr10  = 0x10,
+    Nr11  = 0x11,
+    Nr12  = 0x12,
+    Nr13  = 0x13,
+    Nr14  = 0x14,
+    Nr21  = 0x16,
+    Nr22  = 0x17,
+    Nr23  = 0x18,
+    Nr24  = 0x19,
+    Nr30  = 0x1a,
+    Nr31  = 0x1b,
+    Nr32  = 0x1c,
+    Nr33  = 0x1d,
+    Nr34  = 0x1e,
+    Nr41  = 0x20,
+    Nr42  = 0x21,
+    Nr43  = 0x22,
+    Nr44  = 0x23,
+    Nr50  = 0x24,
+    Nr51  = 0x25,
+    Nr52  = 0x26,
+    Lcdc  = 0x40,
+    Stat  = 0x41,
+    Scy   = 0x42,
+    Scx   = 0x43,
+    Ly    = 0x44,
+    Lyc   = 0x45,
+    Dma   = 0x46,
+    Bgp   = 0x47,
+    Obp0  = 0x48,
+    Obp1  = 0x49,
+    Wy    = 0x4a,
+    Wx    = 0x4b,
+    Key0  = 0x4c,
+    Key1  = 0x4d,
+    Vbk   = 0x4f,
+    Bank  = 0x50,
+    Hdma1 = 0x51,
+    Hdma2 = 0x52,
+    Hdma3 = 0x53,
+    Hdma4 = 0x54,
+    Hdma5 = 0x55,
+    Rp    = 0x56,
+    Bcps  = 0x68,
+    Bcpd  = 0x69,
+    Ocps  = 0x6a,
+    Ocpd  = 0x6b,
+    Opri  = 0x6c,
+    Svbk  = 0x70,
+    Pcm12 = 0x76,
+    Pcm34 = 0x77,
+}
+
+impl Reg {
+    /// Gets the register's bus address.
+    #[must_use]
+    pub fn addr(self) -> u16 {
+        BASE | u16::from(self as u8)
+    }
+
+    /// Checks if the register exists on the specified model.
+    ///
+    /// Values of registers which do not exist should be ignored. Registers
+    /// specific to the Game Boy Color do not exist on invalid models.
+    #[must_use]
+    pub fn exists(self, model: &Model) -> bool {
+        if !self.is_cgb() {
+            return true;
+        }
+        let Ok((family, device, _)) = model.parse() else {
+            return false;
+        };
+        match self {
+            // The infrared port was removed from the Game Boy Advance
+            Self::Rp => family == Family::Cgb && device != Some(Device::Agb),
+            _ => family == Family::Cgb,
+        }
+    }
+
+    /// Checks if the register exists only on Game Boy Color models.
+    fn is_cgb(self) -> bool {
+        matches!(
+            self,
+            Self::Key0
+                | Self::Key1
+                | Self::Vbk
+                | Self::Hdma1
+                | Self::Hdma2
+                | Self::Hdma3
+                | Self::Hdma4
+                | Self::Hdma5
+                | Self::Rp
+                | Self::Bcps
+                | Self::Bcpd
+                | Self::Ocps
+                | Self::Ocpd
+                | Self::Opri
+                | Self::Svbk
+                | Self::Pcm12
+                | Self::Pcm34
+        )
+    }
+}
+
+bitfield! {
+    /// Timer control (TAC).
+    Tac {
+        /// Checks if the timer is enabled.
+        enabled = 2,
+    }
+}
+
+impl Tac {
+    /// Gets the clock select (bits 0-1).
+    #[must_use]
+    pub fn clock(self) -> u8 {
+        self.0 & 0b11
+    }
+}
+
+bitfield! {
+    /// Sound on/off (NR52).
+    Nr52 {
+        /// Checks if channel 1 is on.
+        ch1 = 0,
+        /// Checks if channel 2 is on.
+        ch2 = 1,
+        /// Checks if channel 3 is on.
+        ch3 = 2,
+        /// Checks if channel 4 is on.
+        ch4 = 3,
+        /// Checks if audio is enabled.
+        enabled = 7,
+    }
+}
+
+bitfield! {
+    /// LCD control (LCDC).
+    Lcdc {
+        /// Checks if the background and window are enabled.
+        bg_enabled = 0,
+        /// Checks if objects are enabled.
+        obj_enabled = 1,
+        /// Checks if objects are 8x16 pixels.
+        obj_tall = 2,
+        /// Checks if the background uses the tile map at `0x9c00`.
+        bg_map = 3,
+        /// Checks if tiles are addressed from `0x8000`.
+        tile_data = 4,
+        /// Checks if the window is enabled.
+        win_enabled = 5,
+        /// Checks if the window uses the tile map at `0x9c00`.
+        win_map = 6,
+        /// Checks if the LCD and PPU are enabled.
+        lcd_enabled = 7,
+    }
+}
+
+bitfield! {
+    /// LCD status (STAT).
+    Stat {
+        /// Checks if LY is equal to LYC.
+        lyc_eq = 2,
+        /// Checks if the mode 0 interrupt is selected.
+        mode0_int = 3,
+        /// Checks if the mode 1 interrupt is selected.
+        mode1_int = 4,
+        /// Checks if the mode 2 interrupt is selected.
+        mode2_int = 5,
+        /// Checks if the LYC interrupt is selected.
+        lyc_int = 6,
+    }
+}
+
+impl Stat {
+    /// Gets the PPU mode (bits 0-1).
+    #[must_use]
+    pub fn mode(self) -> u8 {
+        self.0 & 0b11
+    }
+}
+
+bitfield! {
+    /// CPU mode select (KEY0).
+    Key0 {
+        /// Checks if the CPU is in DMG compatibility mode.
+        dmg_mode = 2,
+    }
+}
+
+bitfield! {
+    /// Prepare speed switch (KEY1).
+    Key1 {
+        /// Checks if a speed switch is armed.
+        armed = 0,
+        /// Checks if the CPU is in double speed mode.
+        double_speed = 7,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::tests::core;
+
+    #[test]
+    fn io_works() {
+        let mut core = core();
+        let io = core.reg.io();
+
+        // Registers are indexed by bus address
+        assert_eq!(io[0xff40], 0x91);
+        assert_eq!(io.get(0xff80), None);
+        assert_eq!(io.get(0xfeff), None);
+
+        // Registers are named
+        let lcdc = io.lcdc();
+        assert!(lcdc.lcd_enabled() && lcdc.tile_data() && lcdc.bg_enabled());
+        assert!(!lcdc.win_enabled() && !lcdc.obj_enabled());
+        assert_eq!(io.stat().mode(), 1);
+        assert!(io.nr52().enabled());
+        assert!(!io.boot_mapped());
+
+        // Registers are writable
+        let mut io = core.reg.io_mut();
+        io.set(Reg::Lcdc, Lcdc(0x00));
+        io[0xff50] = 0x00;
+        assert!(!io.as_io().lcdc().lcd_enabled());
+        assert!(io.as_io().boot_mapped());
+    }
+
+    #[test]
+    fn io_exists() {
+        let dmg: Model = "GD".parse().unwrap();
+        let cgb: Model = "CC".parse().unwrap();
+
+        assert_eq!(Reg::Key1.addr(), 0xff4d);
+        assert!(Reg::Lcdc.exists(&dmg) && Reg::Lcdc.exists(&cgb));
+        assert!(!Reg::Key0.exists(&dmg) && Reg::Key0.exists(&cgb));
+        assert!(!Reg::Svbk.exists(&dmg) && Reg::Svbk.exists(&cgb));
+
+        let agb: Model = "CA".parse().unwrap();
+        assert!(Reg::Svbk.exists(&agb) && !Reg::Rp.exists(&agb));
+        assert!(Reg::Rp.exists(&cgb));
+        assert!(!Reg::Key0.exists(&Model(*b"CX  ")));
+    }
+}
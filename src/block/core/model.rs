@@ -0,0 +1,346 @@
+//! Game Boy model identification.
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::{Error, Result};
+
+/// Model identifier.
+///
+/// Four-character string to identify Game Boy models.
+///
+/// 1. The first letter represents mutually-incompatible families of models and
+///    is required. The allowed values are `'G'` for the original Game Boy
+///    family, `'S'` for the Super Game Boy family, and `'C'` for the Game Boy
+///    Color and Advance family.
+/// 2. The second letter represents a specific model within the family, and is
+///    optional (If an implementation does not distinguish between specific
+///    models in a family, a space character may be used). The allowed values
+///    for family G are `'D'` for DMG and `'M'` for MGB; the allowed values for
+///    family S are `'N'` for NTSC, `'P'` for PAL, and `'2'` for SGB2; and the allowed
+///    values for family C are `'C'` for CGB, and `'A'` for the various GBA line
+///    models.
+/// 3. The third letter represents a specific CPU revision within a model, and
+///    is optional (If an implementation does not distinguish between revisions,
+///    a space character may be used). The allowed values for model GD (DMG) are
+///    `'0'` and `'A'`, through `'C'`; the allowed values for model CC (CGB) are `'0'`
+///    and `'A'`, through `'E'`; the allowed values for model CA (AGB, AGS, GBP) are
+///    `'0'`, `'A'` and `'B'`; and for every other model this value must be a space
+///    character.
+/// 4. The last character is used for padding and must be a space character.
+///
+/// # Examples
+///
+/// |  Model   | Description
+/// |----------|-------------
+/// | `"GD  "` | A DMG of an unspecified revision.
+/// | `"CCE "` | A CGB using CPU revision E.
+/// | `"S   "` | Some model of the SGB family.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Model(pub [u8; 4]);
+
+impl Model {
+    /// Constructs a new `Model`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device does not belong to the family, or the
+    /// revision does not exist for the device.
+    pub fn new(family: Family, device: Option<Device>, rev: Option<Revision>) -> Result<Self> {
+        let model = Self([
+            family.to_byte(),
+            device.map_or(b' ', Device::to_byte),
+            rev.map_or(b' ', Revision::to_byte),
+            b' ',
+        ]);
+        model.check().map(|()| model)
+    }
+
+    /// Parses into the family, device and revision.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the identifier is invalid.
+    pub fn parse(&self) -> Result<(Family, Option<Device>, Option<Revision>)> {
+        let err = || Error::Model(*self);
+        let [family, device, rev, pad] = self.0;
+        // Parse each character
+        let family = Family::from_byte(family).ok_or_else(err)?;
+        let device = match device {
+            b' ' => None,
+            byte => Some(Device::from_byte(byte).ok_or_else(err)?),
+        };
+        let rev = match rev {
+            b' ' => None,
+            byte => Some(Revision::from_byte(byte).ok_or_else(err)?),
+        };
+        // Check each combination
+        if device.is_some_and(|device| device.family() != family)
+            || rev.is_some_and(|rev| !device.is_some_and(|device| device.has(rev)))
+            || pad != b' '
+        {
+            return Err(err());
+        }
+        Ok((family, device, rev))
+    }
+
+    /// Checks that the identifier is valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the identifier is invalid.
+    pub fn check(&self) -> Result<()> {
+        self.parse().map(|_| ())
+    }
+
+    /// Gets the family of models.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the identifier is invalid.
+    pub fn family(&self) -> Result<Family> {
+        self.parse().map(|(family, ..)| family)
+    }
+
+    /// Gets the specific model, if specified.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the identifier is invalid.
+    pub fn device(&self) -> Result<Option<Device>> {
+        self.parse().map(|(_, device, _)| device)
+    }
+
+    /// Gets the CPU revision, if specified.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the identifier is invalid.
+    pub fn revision(&self) -> Result<Option<Revision>> {
+        self.parse().map(|(.., rev)| rev)
+    }
+
+    /// Checks if this is an original Game Boy family model.
+    #[must_use]
+    pub fn is_gb(&self) -> bool {
+        self.family().is_ok_and(|family| family == Family::Gb)
+    }
+
+    /// Checks if this is a Super Game Boy family model.
+    #[must_use]
+    pub fn is_sgb(&self) -> bool {
+        self.family().is_ok_and(|family| family == Family::Sgb)
+    }
+
+    /// Checks if this is a Game Boy Color (or Advance) family model.
+    #[must_use]
+    pub fn is_cgb(&self) -> bool {
+        self.family().is_ok_and(|family| family == Family::Cgb)
+    }
+}
+
+impl Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0
+            .iter()
+            .try_for_each(|&byte| write!(f, "{}", char::from(byte)))
+    }
+}
+
+impl FromStr for Model {
+    type Err = Error;
+
+    /// Parses a model identifier, padding it with spaces.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut buf = [b' '; 4];
+        let len = s.len().min(buf.len());
+        buf[..len].copy_from_slice(&s.as_bytes()[..len]);
+        let model = Self(buf);
+        if s.is_empty() || s.len() > buf.len() {
+            return Err(Error::Model(model));
+        }
+        model.check().map(|()| model)
+    }
+}
+
+/// Mutually-incompatible families of models.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Family {
+    /// Original Game Boy family (`'G'`).
+    Gb,
+    /// Super Game Boy family (`'S'`).
+    Sgb,
+    /// Game Boy Color and Advance family (`'C'`).
+    Cgb,
+}
+
+impl Family {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'G' => Some(Self::Gb),
+            b'S' => Some(Self::Sgb),
+            b'C' => Some(Self::Cgb),
+            _ => None,
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Gb => b'G',
+            Self::Sgb => b'S',
+            Self::Cgb => b'C',
+        }
+    }
+}
+
+/// Specific models within a family.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Device {
+    /// Game Boy (`"GD"`).
+    Dmg,
+    /// Game Boy Pocket (`"GM"`).
+    Mgb,
+    /// Super Game Boy, NTSC (`"SN"`).
+    Ntsc,
+    /// Super Game Boy, PAL (`"SP"`).
+    Pal,
+    /// Super Game Boy 2 (`"S2"`).
+    Sgb2,
+    /// Game Boy Color (`"CC"`).
+    Cgb,
+    /// Game Boy Advance line (`"CA"`).
+    Agb,
+}
+
+impl Device {
+    /// Gets the family this model belongs to.
+    #[must_use]
+    pub fn family(self) -> Family {
+        match self {
+            Self::Dmg | Self::Mgb => Family::Gb,
+            Self::Ntsc | Self::Pal | Self::Sgb2 => Family::Sgb,
+            Self::Cgb | Self::Agb => Family::Cgb,
+        }
+    }
+
+    /// Checks if a CPU revision exists for this model.
+    #[must_use]
+    pub fn has(self, rev: Revision) -> bool {
+        use Revision::{Rev0, RevA, RevB, RevC, RevD, RevE};
+
+        match self {
+            Self::Dmg => matches!(rev, Rev0 | RevA | RevB | RevC),
+            Self::Cgb => matches!(rev, Rev0 | RevA | RevB | RevC | RevD | RevE),
+            Self::Agb => matches!(rev, Rev0 | RevA | RevB),
+            _ => false,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'D' => Some(Self::Dmg),
+            b'M' => Some(Self::Mgb),
+            b'N' => Some(Self::Ntsc),
+            b'P' => Some(Self::Pal),
+            b'2' => Some(Self::Sgb2),
+            b'C' => Some(Self::Cgb),
+            b'A' => Some(Self::Agb),
+            _ => None,
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Dmg => b'D',
+            Self::Mgb => b'M',
+            Self::Ntsc => b'N',
+            Self::Pal => b'P',
+            Self::Sgb2 => b'2',
+            Self::Cgb => b'C',
+            Self::Agb => b'A',
+        }
+    }
+}
+
+/// CPU revisions.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Revision {
+    /// Revision 0 (`'0'`).
+    Rev0,
+    /// Revision A (`'A'`).
+    RevA,
+    /// Revision B (`'B'`).
+    RevB,
+    /// Revision C (`'C'`).
+    RevC,
+    /// Revision D (`'D'`).
+    RevD,
+    /// Revision E (`'E'`).
+    RevE,
+}
+
+impl Revision {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'0' => Some(Self::Rev0),
+            b'A' => Some(Self::RevA),
+            b'B' => Some(Self::RevB),
+            b'C' => Some(Self::RevC),
+            b'D' => Some(Self::RevD),
+            b'E' => Some(Self::RevE),
+            _ => None,
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Rev0 => b'0',
+            Self::RevA => b'A',
+            Self::RevB => b'B',
+            Self::RevC => b'C',
+            Self::RevD => b'D',
+            Self::RevE => b'E',
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_parse_works() {
+        let found: Model = "CCE".parse().unwrap();
+        assert_eq!(found, Model(*b"CCE "));
+        assert_eq!(
+            found.parse().unwrap(),
+            (Family::Cgb, Some(Device::Cgb), Some(Revision::RevE))
+        );
+        assert!(found.is_cgb());
+        assert_eq!(found.to_string(), "CCE ");
+
+        let found = Model::new(Family::Sgb, Some(Device::Sgb2), None).unwrap();
+        assert_eq!(found, Model(*b"S2  "));
+        assert!(found.is_sgb());
+    }
+
+    #[test]
+    fn model_parse_invalid() {
+        // Revision E does not exist on a DMG
+        assert!(matches!("GDE".parse::<Model>(), Err(Error::Model(_))));
+        // Revisions require a model
+        assert!("G A".parse::<Model>().is_err());
+        // Models must belong to the family
+        assert!(Model::new(Family::Gb, Some(Device::Cgb), None).is_err());
+        // Padding must be a space
+        assert!(Model(*b"GD0!").check().is_err());
+        assert!(matches!("".parse::<Model>(), Err(Error::Model(_))));
+        assert!(matches!(
+            "GD0  ".parse::<Model>(),
+            Err(Error::Model(Model(model))) if model == *b"GD0 "
+        ));
+        // Invalid models belong to no family
+        assert!(!Model(*b"CX  ").is_cgb());
+    }
+}
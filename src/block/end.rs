@@ -1,6 +1,6 @@
 //! Mark the end of BESS data.
 
-use super::{Data, Ident};
+use super::{Data, Ident, Typed};
 
 /// `END` block.
 ///
@@ -13,15 +13,13 @@ use super::{Data, Ident};
 pub struct End;
 
 impl End {
-    /// Identifier for this block.
-    const IDENT: Ident = Ident::new(*b"END ");
     /// Constant length of this block.
     const LEN: u32 = 0;
 }
 
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Data for End {
-    fn ident() -> Ident {
+    fn ident(&self) -> Ident {
         Self::IDENT
     }
 
@@ -29,3 +27,7 @@ impl Data for End {
         Self::LEN
     }
 }
+
+impl Typed for End {
+    const IDENT: Ident = Ident::new(*b"END ");
+}
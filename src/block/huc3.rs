@@ -0,0 +1,52 @@
+//! HuC-3 clock and alarm state.
+
+use super::{Data, Ident, Typed};
+
+/// `HUC3` block.
+///
+/// Contains the state of the HuC-3's real-time clock and alarm. This is an
+/// *optional* block.
+///
+/// Should only be used for cartridges using a HuC-3.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug)]
+pub struct Huc3 {
+    /// UNIX timestamp at the time of the save state.
+    pub timestamp: u64,
+    /// Current clock counters.
+    pub clock: Counter,
+    /// Scheduled alarm counters.
+    pub alarm: Counter,
+    /// Whether the alarm is enabled.
+    pub enabled: bool,
+}
+
+impl Huc3 {
+    /// Constant length of this block.
+    const LEN: u32 = 0x11;
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Data for Huc3 {
+    fn ident(&self) -> Ident {
+        Self::IDENT
+    }
+
+    fn len(&self) -> u32 {
+        Self::LEN
+    }
+}
+
+impl Typed for Huc3 {
+    const IDENT: Ident = Ident::new(*b"HUC3");
+}
+
+/// Time counters.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Counter {
+    /// Minutes counter.
+    pub minutes: u16,
+    /// Days counter.
+    pub days: u16,
+}
@@ -1,6 +1,6 @@
 //! Information about the ROM this save state originates from.
 
-use super::{Data, Ident};
+use super::{Data, Ident, Typed};
 
 type Title = [u8; 16];
 
@@ -12,7 +12,7 @@ type Title = [u8; 16];
 /// When used, this block should come before `CORE` but after `NAME`.
 #[allow(unused)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct Info {
     /// Title provided by the ROM header.
     ///
@@ -25,8 +25,6 @@ pub struct Info {
 }
 
 impl Info {
-    /// Identifier for this block.
-    const IDENT: Ident = Ident::new(*b"INFO");
     /// Constant length of this block.
     const LEN: u32 = 0x12;
 }
@@ -39,11 +37,23 @@ impl Info {
             gchk,
         }
     }
+
+    /// Gets the title provided by the ROM header.
+    #[must_use]
+    pub fn title(&self) -> &Title {
+        &self.title
+    }
+
+    /// Gets the global checksum provided by the ROM header.
+    #[must_use]
+    pub fn gchk(&self) -> u16 {
+        self.gchk
+    }
 }
 
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Data for Info {
-    fn ident() -> Ident {
+    fn ident(&self) -> Ident {
         Self::IDENT
     }
 
@@ -51,3 +61,7 @@ impl Data for Info {
         Self::LEN
     }
 }
+
+impl Typed for Info {
+    const IDENT: Ident = Ident::new(*b"INFO");
+}
@@ -0,0 +1,79 @@
+//! Memory bank controller state.
+
+use super::{Data, Ident, Typed};
+use crate::{Error, Result};
+
+/// `MBC` block.
+///
+/// Contains a list of writes to MBC registers which, when replayed in order,
+/// restore the state of the MBC. This is an *optional* block.
+///
+/// Writes may only target the ROM region (`0x0000` to `0x7FFF`) or the MBC RAM
+/// enable/disable region (`0xA000` to `0xBFFF`).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Vec<Write>"))]
+#[derive(Debug, Default)]
+pub struct Mbc(Vec<Write>);
+
+impl Mbc {
+    /// Constructs a new `Mbc`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are too many writes for the block's length.
+    pub fn new(writes: impl Into<Vec<Write>>) -> Result<Self> {
+        let writes = writes.into();
+        writes
+            .len()
+            .checked_mul(Write::LEN)
+            .and_then(|len| u32::try_from(len).ok())
+            .ok_or(Error::TooLarge)?;
+        Ok(Self(writes))
+    }
+
+    /// Gets the list of register writes.
+    #[must_use]
+    pub fn writes(&self) -> &[Write] {
+        &self.0
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Data for Mbc {
+    fn ident(&self) -> Ident {
+        Self::IDENT
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn len(&self) -> u32 {
+        // Checked upon construction
+        (self.0.len() * Write::LEN) as u32
+    }
+}
+
+impl Typed for Mbc {
+    const IDENT: Ident = Ident::new(*b"MBC ");
+}
+
+impl TryFrom<Vec<Write>> for Mbc {
+    type Error = Error;
+
+    fn try_from(writes: Vec<Write>) -> Result<Self> {
+        Self::new(writes)
+    }
+}
+
+/// Register write.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Write {
+    /// Address being written to.
+    pub addr: u16,
+    /// Value being written.
+    pub data: u8,
+}
+
+impl Write {
+    /// Constant length of a write.
+    const LEN: usize = 3;
+}
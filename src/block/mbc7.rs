@@ -0,0 +1,107 @@
+//! MBC7 accelerometer and EEPROM state.
+
+use super::{Data, Ident, Typed};
+
+/// `MBC7` block.
+///
+/// Contains the state of the MBC7's accelerometer and EEPROM interface. This is
+/// an *optional* block.
+///
+/// Should only be used for cartridges using an MBC7. The contents of the EEPROM
+/// itself are not stored in this block; they are instead saved as the MBC RAM,
+/// pointed to by the [`eram`](super::core::Locations::eram) location of the
+/// `CORE` block.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug)]
+pub struct Mbc7 {
+    /// Latch and EEPROM line flags.
+    pub flags: Flags,
+    /// Number of argument bits left.
+    pub argc: u8,
+    /// Current EEPROM command.
+    pub command: u16,
+    /// Pending bits to read.
+    pub read: u16,
+    /// Latched accelerometer X value.
+    pub x: u16,
+    /// Latched accelerometer Y value.
+    pub y: u16,
+}
+
+impl Mbc7 {
+    /// Constant length of this block.
+    const LEN: u32 = 0x0a;
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Data for Mbc7 {
+    fn ident(&self) -> Ident {
+        Self::IDENT
+    }
+
+    fn len(&self) -> u32 {
+        Self::LEN
+    }
+}
+
+impl Typed for Mbc7 {
+    const IDENT: Ident = Ident::new(*b"MBC7");
+}
+
+/// MBC7 flags.
+///
+/// | Bit | Flag
+/// |-----|------
+/// |  0  | Latch ready
+/// |  1  | EEPROM DO line
+/// |  2  | EEPROM DI line
+/// |  3  | EEPROM CLK line
+/// |  4  | EEPROM CS line
+/// |  5  | EEPROM write enable
+/// | 6-7 | Unused (must be 0)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Flags(pub u8);
+
+impl Flags {
+    /// Checks if the accelerometer latch is ready.
+    #[must_use]
+    pub fn latch_ready(self) -> bool {
+        self.bit(0)
+    }
+
+    /// Checks the EEPROM DO line.
+    #[must_use]
+    pub fn eeprom_do(self) -> bool {
+        self.bit(1)
+    }
+
+    /// Checks the EEPROM DI line.
+    #[must_use]
+    pub fn eeprom_di(self) -> bool {
+        self.bit(2)
+    }
+
+    /// Checks the EEPROM CLK line.
+    #[must_use]
+    pub fn eeprom_clk(self) -> bool {
+        self.bit(3)
+    }
+
+    /// Checks the EEPROM CS line.
+    #[must_use]
+    pub fn eeprom_cs(self) -> bool {
+        self.bit(4)
+    }
+
+    /// Checks if writing to the EEPROM is enabled.
+    #[must_use]
+    pub fn eeprom_we(self) -> bool {
+        self.bit(5)
+    }
+
+    /// Checks the specified bit.
+    fn bit(self, idx: u8) -> bool {
+        self.0 & (1 << idx) != 0
+    }
+}
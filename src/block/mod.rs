@@ -2,18 +2,27 @@
 
 #![allow(clippy::len_without_is_empty)]
 
+use std::any::Any;
 use std::fmt::{Debug, Display};
 
 pub mod core;
 pub mod end;
+pub mod huc3;
 pub mod info;
+pub mod mbc;
+pub mod mbc7;
 pub mod name;
+pub mod raw;
+pub mod rtc;
+pub mod sgb;
+pub mod tpp1;
+pub mod xoam;
 
 /// Block kind identifier.
 ///
 /// Unique four-letter ASCII identifier.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Ident([u8; 4]);
 
 impl Ident {
@@ -32,12 +41,32 @@ impl Display for Ident {
 
 /// Complete block structure.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "Parts"))]
 #[derive(Debug)]
 pub struct Block {
     head: Header,
     body: Box<dyn Data>,
 }
 
+/// Deserialized block structure, prior to restoring raw identifiers.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct Parts {
+    head: Header,
+    body: Box<dyn Data>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Parts> for Block {
+    fn from(Parts { head, mut body }: Parts) -> Self {
+        // Raw bodies don't serialize their identifier
+        if let Some(raw) = body.as_any_mut().downcast_mut::<raw::Raw>() {
+            raw.ident = head.ident;
+        }
+        Self { head, body }
+    }
+}
+
 impl Block {
     /// Gets the block's identifier.
     #[must_use]
@@ -55,6 +84,14 @@ impl Block {
     pub const fn body(&self) -> &dyn Data {
         &*self.body
     }
+
+    /// Gets the block's body as a concrete type.
+    ///
+    /// Returns `None` if the body is not of type `T`.
+    #[must_use]
+    pub fn get<T: Data>(&self) -> Option<&T> {
+        self.body().as_any().downcast_ref()
+    }
 }
 
 impl<T: Data + 'static> From<T> for Block {
@@ -68,7 +105,7 @@ impl<T: Data + 'static> From<T> for Block {
 
 /// Header preceding block data.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct Header {
     /// Unique identifier specifying the block type.
     ident: Ident,
@@ -76,25 +113,97 @@ pub struct Header {
     len: u32,
 }
 
+impl Header {
+    /// Gets the header's identifier.
+    #[must_use]
+    pub const fn ident(&self) -> &Ident {
+        &self.ident
+    }
+
+    /// Gets the header's length.
+    #[must_use]
+    pub const fn len(&self) -> u32 {
+        self.len
+    }
+}
+
 /// Block body containing data.
 #[cfg_attr(feature = "serde", typetag::serde)]
-pub trait Data: Debug {
+pub trait Data: AsAny + Debug {
     /// Gets this block's identifier.
-    fn ident() -> Ident
-    where
-        Self: Sized;
+    fn ident(&self) -> Ident;
 
-    /// Gets this block's identifier.
+    /// Gets this block's length.
     fn len(&self) -> u32;
 
     /// Generates the header for this body.
-    fn header(&self) -> Header
-    where
-        Self: Sized,
-    {
+    fn header(&self) -> Header {
         Header {
-            ident: Self::ident(),
+            ident: self.ident(),
             len: self.len(),
         }
     }
 }
+
+/// Block body with a statically known identifier.
+///
+/// Implemented by every block type except [`Raw`](raw::Raw), whose
+/// identifier is only known at runtime.
+pub trait Typed: Data {
+    /// Identifier for this block type.
+    const IDENT: Ident;
+}
+
+/// Upcasting to [`Any`], allowing block bodies to be downcast.
+pub trait AsAny: Any {
+    /// Gets this value as `&dyn Any`.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Gets this value as `&mut dyn Any`.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mbc::Mbc;
+    use super::*;
+    use crate::build::tests::setup;
+    use crate::Issue;
+
+    #[test]
+    fn length_validated() {
+        let mut test = setup();
+        // Headers may be deserialized independently of their bodies
+        test.blx.insert(
+            3,
+            Block {
+                head: Header {
+                    ident: Mbc::IDENT,
+                    len: 3,
+                },
+                body: Box::new(Mbc::default()),
+            },
+        );
+        let found: Vec<_> = test
+            .validate()
+            .into_iter()
+            .filter(|diag| matches!(diag.issue, Issue::Length { .. }))
+            .map(|diag| diag.to_string())
+            .collect();
+
+        assert_eq!(
+            found,
+            ["error: block length does not match type: `MBC ` (0x3 bytes)"]
+        );
+    }
+}
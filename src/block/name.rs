@@ -1,6 +1,6 @@
 //! Name of the emulator that created this save state.
 
-use super::{Data, Ident};
+use super::{Data, Ident, Typed};
 
 /// `NAME` block.
 ///
@@ -16,11 +16,6 @@ use super::{Data, Ident};
 #[derive(Debug)]
 pub struct Name(String);
 
-impl Name {
-    /// Identifier for this block.
-    const IDENT: Ident = Ident::new(*b"NAME");
-}
-
 impl Name {
     /// Constructs a new `Name`.
     #[allow(clippy::needless_pass_by_value)]
@@ -31,7 +26,7 @@ impl Name {
 
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Data for Name {
-    fn ident() -> Ident {
+    fn ident(&self) -> Ident {
         Self::IDENT
     }
 
@@ -39,3 +34,7 @@ impl Data for Name {
         u32::try_from(self.0.len()).unwrap()
     }
 }
+
+impl Typed for Name {
+    const IDENT: Ident = Ident::new(*b"NAME");
+}
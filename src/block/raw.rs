@@ -0,0 +1,64 @@
+//! Opaque data for unrecognized blocks.
+
+#[cfg(feature = "serde")]
+use serde_with::{As, Bytes};
+
+use super::{Data, Ident};
+
+/// Raw block.
+///
+/// Contains the unparsed body of a block whose identifier is not recognized,
+/// such as an implementation-specific extension, or a block from a newer
+/// version of the specification. Preserving these allows them to be written
+/// back unchanged.
+///
+/// Unlike other blocks, the identifier of a raw block is not known statically,
+/// so it is stored alongside the body and returned by [`Data::ident`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug)]
+pub struct Raw {
+    /// Identifier of the block.
+    ///
+    /// Already present in the block's header, so it is not serialized. When
+    /// deserializing, it is restored from the header by the enclosing block.
+    #[cfg_attr(feature = "serde", serde(skip, default = "Raw::unset"))]
+    pub(super) ident: Ident,
+    /// Contents of the block.
+    #[cfg_attr(feature = "serde", serde(with = "As::<Bytes>"))]
+    bytes: Vec<u8>,
+}
+
+impl Raw {
+    /// Gets an unset identifier, to be restored by the enclosing block.
+    #[cfg(feature = "serde")]
+    fn unset() -> Ident {
+        Ident::new([0; 4])
+    }
+}
+
+impl Raw {
+    /// Constructs a new `Raw`.
+    pub fn new(ident: Ident, bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            ident,
+            bytes: bytes.into(),
+        }
+    }
+
+    /// Gets the block's contents.
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Data for Raw {
+    fn ident(&self) -> Ident {
+        self.ident
+    }
+
+    fn len(&self) -> u32 {
+        u32::try_from(self.bytes.len()).unwrap()
+    }
+}
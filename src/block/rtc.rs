@@ -0,0 +1,58 @@
+//! MBC3 real-time clock state.
+
+use super::{Data, Ident, Typed};
+
+/// `RTC` block.
+///
+/// Contains the state of the MBC3's real-time clock. This is an *optional*
+/// block.
+///
+/// Should only be used for cartridges using an MBC3 with an RTC.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug)]
+pub struct Rtc {
+    /// Current clock registers.
+    pub current: Clock,
+    /// Latched clock registers.
+    pub latched: Clock,
+    /// UNIX timestamp at the time of the save state.
+    pub timestamp: u64,
+}
+
+impl Rtc {
+    /// Constant length of this block.
+    const LEN: u32 = 0x30;
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Data for Rtc {
+    fn ident(&self) -> Ident {
+        Self::IDENT
+    }
+
+    fn len(&self) -> u32 {
+        Self::LEN
+    }
+}
+
+impl Typed for Rtc {
+    const IDENT: Ident = Ident::new(*b"RTC ");
+}
+
+/// Clock registers.
+///
+/// Each register is stored as a 32-bit little-endian value.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug)]
+pub struct Clock {
+    /// Seconds register.
+    pub sec: u32,
+    /// Minutes register.
+    pub min: u32,
+    /// Hours register.
+    pub hour: u32,
+    /// Days register (lower 8 bits).
+    pub days: u32,
+    /// High register (upper day bit, halt and day carry flags).
+    pub high: u32,
+}
@@ -0,0 +1,122 @@
+//! Super Game Boy state.
+
+use super::core::Pointer;
+use super::{Data, Ident, Typed};
+use crate::Result;
+
+/// `SGB` block.
+///
+/// Contains the state of the Super Game Boy's border, colorization and
+/// multiplayer features. This is an *optional* block.
+///
+/// Should only be used when emulating a model from the SGB family. Like the
+/// [`Locations`](super::core::Locations) of the `CORE` block, large buffers are
+/// stored outside of the BESS structure, and are referred to by pointers.
+/// Implementations should fall back to sane defaults for any buffer with a
+/// pointer length of 0.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug)]
+pub struct Sgb {
+    /// Border tile data pointer (SNES tile format, 0x2000 bytes).
+    pub border_tiles: Pointer,
+    /// Border tilemap pointer (little-endian 16-bit entries, 0x800 bytes).
+    pub border_tilemap: Pointer,
+    /// Border palettes pointer (little-endian 16-bit entries, 0x80 bytes).
+    pub border_palettes: Pointer,
+    /// Active colorization palettes pointer (little-endian 16-bit entries, 0x20
+    /// bytes).
+    pub active_palettes: Pointer,
+    /// RAM colorization palettes pointer (little-endian 16-bit entries, 0x1000
+    /// bytes).
+    pub ram_palettes: Pointer,
+    /// Attribute map pointer (0x168 bytes).
+    pub attribute_map: Pointer,
+    /// Attribute files pointer (0xfe0 bytes).
+    pub attribute_files: Pointer,
+    /// Multiplayer status.
+    pub multiplayer: Multiplayer,
+}
+
+impl Sgb {
+    /// Constant length of this block.
+    const LEN: u32 = 0x39;
+}
+
+impl Sgb {
+    /// Checks that every pointer lies within the context buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any pointer is out of bounds.
+    pub fn check(&self, ctx: &[u8]) -> Result<()> {
+        self.check_len(ctx.len())
+    }
+
+    /// Checks that every pointer lies within a context of `len` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any pointer is out of bounds.
+    pub fn check_len(&self, len: usize) -> Result<()> {
+        [
+            &self.border_tiles,
+            &self.border_tilemap,
+            &self.border_palettes,
+            &self.active_palettes,
+            &self.ram_palettes,
+            &self.attribute_map,
+            &self.attribute_files,
+        ]
+        .into_iter()
+        .try_for_each(|ptr| ptr.range(len).map(|_| ()))
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Data for Sgb {
+    fn ident(&self) -> Ident {
+        Self::IDENT
+    }
+
+    fn len(&self) -> u32 {
+        Self::LEN
+    }
+}
+
+impl Typed for Sgb {
+    const IDENT: Ident = Ident::new(*b"SGB ");
+}
+
+/// Multiplayer status.
+///
+/// The high nibble is the number of players, while the low nibble is the
+/// current player (0-based).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Multiplayer(pub u8);
+
+impl Multiplayer {
+    /// Constructs a new `Multiplayer`.
+    #[must_use]
+    pub const fn new(players: u8, player: u8) -> Self {
+        Self((players << 4) | (player & 0x0f))
+    }
+
+    /// Gets the number of players.
+    #[must_use]
+    pub const fn players(self) -> u8 {
+        self.0 >> 4
+    }
+
+    /// Gets the current player (0-based).
+    #[must_use]
+    pub const fn player(self) -> u8 {
+        self.0 & 0x0f
+    }
+}
+
+impl Default for Multiplayer {
+    fn default() -> Self {
+        Self::new(1, 0)
+    }
+}
@@ -0,0 +1,45 @@
+//! TPP1 real-time clock state.
+
+use super::{Data, Ident, Typed};
+
+/// `TPP1` block.
+///
+/// Contains the state of the TPP1's real-time clock. This is an *optional*
+/// block.
+///
+/// Should only be used for cartridges using a TPP1 mapper with an RTC.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug)]
+pub struct Tpp1 {
+    /// UNIX timestamp at the time of the save state.
+    pub timestamp: u64,
+    /// Current RTC data, as the 4 RTC bytes from the TPP1 specification.
+    pub current: Clock,
+    /// Latched RTC data, as the 4 RTC bytes from the TPP1 specification.
+    pub latched: Clock,
+    /// Value of the MR4 register.
+    pub mr4: u8,
+}
+
+impl Tpp1 {
+    /// Constant length of this block.
+    const LEN: u32 = 0x11;
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Data for Tpp1 {
+    fn ident(&self) -> Ident {
+        Self::IDENT
+    }
+
+    fn len(&self) -> u32 {
+        Self::LEN
+    }
+}
+
+impl Typed for Tpp1 {
+    const IDENT: Ident = Ident::new(*b"TPP1");
+}
+
+/// RTC data bytes.
+pub type Clock = [u8; 4];
@@ -0,0 +1,38 @@
+//! Extra OAM memory.
+
+#[cfg(feature = "serde")]
+use serde_with::{As, Bytes};
+
+use super::{Data, Ident, Typed};
+
+/// `XOAM` block.
+///
+/// Contains the 0x60 bytes of extra OAM memory found in the unusable region
+/// from `0xFEA0` to `0xFEFF`, as found on some models. This is an *optional*
+/// block.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug)]
+pub struct Xoam(#[cfg_attr(feature = "serde", serde(with = "As::<Bytes>"))] pub Extra);
+
+impl Xoam {
+    /// Constant length of this block.
+    const LEN: u32 = 0x60;
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Data for Xoam {
+    fn ident(&self) -> Ident {
+        Self::IDENT
+    }
+
+    fn len(&self) -> u32 {
+        Self::LEN
+    }
+}
+
+impl Typed for Xoam {
+    const IDENT: Ident = Ident::new(*b"XOAM");
+}
+
+/// Extra OAM memory (96 bytes).
+pub type Extra = [u8; 0x60];
@@ -1,10 +1,20 @@
+use std::collections::BTreeMap;
 use std::iter;
 
-use crate::block::core::Core;
+use crate::block::core::{Core, Pointer, Region, Version};
 use crate::block::end::End;
+use crate::block::huc3::Huc3;
 use crate::block::info::Info;
+use crate::block::mbc::Mbc;
+use crate::block::mbc7::Mbc7;
 use crate::block::name::Name;
-use crate::{Bess, Block, Data, Error, Footer, Result};
+use crate::block::rtc::Rtc;
+use crate::block::sgb::Sgb;
+use crate::block::tpp1::Tpp1;
+use crate::block::xoam::Xoam;
+#[cfg(feature = "serde")]
+use crate::de::Registry;
+use crate::{Bess, Block, Data, Error, Footer, Result, Typed};
 
 impl Bess {
     // Create a builder for `Bess`.
@@ -21,22 +31,93 @@ pub struct Builder {
     info: Option<Info>,
     core: Needed<Core>,
     xtra: Vec<Block>,
+    mem: BTreeMap<Region, Vec<u8>>,
+    version: Option<Version>,
+    #[cfg(feature = "serde")]
+    reg: Registry,
 }
 
 impl Builder {
     /// Builds a new `Bess`.
+    ///
+    /// Any memory buffers are placed after the context, just before the
+    /// blocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required block is missing, an extra block
+    /// conflicts with a known block type, or a pointer lies beyond the
+    /// context.
     pub fn build(self, ctx: impl Into<Vec<u8>>) -> Result<Bess> {
         // Prepare context buffer
-        let ctx = ctx.into();
+        let mut ctx = ctx.into();
+        // Build blocks
+        let (mem, blx) = self.parts(ctx.len())?;
+        ctx.extend(mem);
+        // Check the locations
+        if let Some(core) = blx.iter().find_map(Block::get::<Core>) {
+            core.mem.check(&ctx)?;
+        }
+        for sgb in blx.iter().filter_map(Block::get::<Sgb>) {
+            sgb.check(&ctx)?;
+        }
+        // Calculate footer
+        let end = Footer::new(u32::try_from(ctx.len()).map_err(|_| Error::TooLarge)?);
+        // Build and return
+        Ok(Bess {
+            ctx,
+            blx,
+            end,
+            fit: BTreeMap::new(),
+        })
+    }
+
+    /// Builds the memory buffers to be placed at `start`, along with the
+    /// blocks, finishing with an `END` block.
+    pub(crate) fn parts(self, start: usize) -> Result<(Vec<u8>, Vec<Block>)> {
         // Extract parts
         let Self {
             name,
             info,
             core,
             xtra,
+            mem,
+            version,
+            #[cfg(feature = "serde")]
+            reg,
         } = self;
         // Check needed fields
-        let core = core.get()?;
+        let mut core = core.get()?;
+        // Check extra blocks
+        for blk in &xtra {
+            let ident = *blk.ident();
+            // Reject blocks placed by the builder
+            if ident == Core::IDENT || ident == End::IDENT {
+                return Err(Error::Registered(ident));
+            }
+            // Reject conflicts with known block types
+            #[cfg(feature = "serde")]
+            if reg.is_known(ident) && !reg.holds(blk) {
+                return Err(Error::Registered(ident));
+            }
+        }
+        // Override the version
+        if let (Some(core), Some(version)) = (&mut core, version) {
+            core.version = version;
+        }
+        // Lay out memory buffers
+        let mut buf = Vec::new();
+        for (region, data) in mem {
+            let ptr = start.checked_add(buf.len()).ok_or(Error::TooLarge)?;
+            let ptr = Pointer {
+                len: u32::try_from(data.len()).map_err(|_| Error::TooLarge)?,
+                ptr: u32::try_from(ptr).map_err(|_| Error::TooLarge)?,
+            };
+            if let Some(core) = &mut core {
+                *core.mem.get_mut(region) = ptr;
+            }
+            buf.extend(data);
+        }
         // Convert to blocks
         let name = name.map(Block::from);
         let info = info.map(Block::from);
@@ -52,10 +133,7 @@ impl Builder {
             // Then, finish with end block
             .chain(iter::once(End.into()))
             .collect();
-        // Calculate footer
-        let end = Footer::new(u32::try_from(ctx.len()).map_err(|_| Error::TooLarge)?);
-        // Build and return
-        Ok(Bess { ctx, blx, end })
+        Ok((buf, blx))
     }
 
     pub fn name(mut self, name: impl ToString) -> Self {
@@ -68,22 +146,106 @@ impl Builder {
         self
     }
 
+    /// Sets the `CORE` block's version, overriding that of the block.
+    ///
+    /// Otherwise, the block's own version is kept, which defaults to
+    /// [`Version::CURRENT`] when constructed with [`Core::new`].
+    pub fn version(mut self, version: Version) -> Self {
+        self.version = Some(version);
+        self
+    }
+
     pub fn core(mut self, core: Core) -> Self {
         self.core = Needed(core.into());
         self
     }
 
+    pub fn mbc(self, mbc: Mbc) -> Self {
+        self.block(mbc)
+    }
+
+    pub fn rtc(self, rtc: Rtc) -> Self {
+        self.block(rtc)
+    }
+
+    pub fn huc3(self, huc3: Huc3) -> Self {
+        self.block(huc3)
+    }
+
+    pub fn tpp1(self, tpp1: Tpp1) -> Self {
+        self.block(tpp1)
+    }
+
+    pub fn mbc7(self, mbc7: Mbc7) -> Self {
+        self.block(mbc7)
+    }
+
+    pub fn sgb(self, sgb: Sgb) -> Self {
+        self.block(sgb)
+    }
+
+    pub fn xoam(self, xoam: Xoam) -> Self {
+        self.block(xoam)
+    }
+
+    pub fn wram(self, buf: impl Into<Vec<u8>>) -> Self {
+        self.memory(Region::Wram, buf)
+    }
+
+    pub fn vram(self, buf: impl Into<Vec<u8>>) -> Self {
+        self.memory(Region::Vram, buf)
+    }
+
+    pub fn eram(self, buf: impl Into<Vec<u8>>) -> Self {
+        self.memory(Region::Eram, buf)
+    }
+
+    pub fn oam(self, buf: impl Into<Vec<u8>>) -> Self {
+        self.memory(Region::Oam, buf)
+    }
+
+    pub fn hram(self, buf: impl Into<Vec<u8>>) -> Self {
+        self.memory(Region::Hram, buf)
+    }
+
+    pub fn bgp(self, buf: impl Into<Vec<u8>>) -> Self {
+        self.memory(Region::Bgp, buf)
+    }
+
+    pub fn obj(self, buf: impl Into<Vec<u8>>) -> Self {
+        self.memory(Region::Obj, buf)
+    }
+
+    /// Adds a memory buffer, overriding its pointer within the `CORE` block.
+    pub fn memory(mut self, region: Region, buf: impl Into<Vec<u8>>) -> Self {
+        self.mem.insert(region, buf.into());
+        self
+    }
+
+    /// Adds an extra block, placed after the `CORE` block.
+    ///
+    /// Building fails if the block is a `CORE` or `END` block, or its type
+    /// differs from the one known for its identifier.
     pub fn block<T: Data + 'static>(mut self, body: T) -> Self {
         self.xtra.push(Block::from(body));
         self
     }
+
+    /// Sets the registry of block types checked against extra blocks.
+    ///
+    /// Defaults to only the built-in block types.
+    #[cfg(feature = "serde")]
+    pub fn registry(mut self, reg: &Registry) -> Self {
+        self.reg = reg.clone();
+        self
+    }
 }
 
 /// Wrapper around a required field.
 #[derive(Debug)]
 struct Needed<T: Data>(Option<T>);
 
-impl<T: Data> Needed<T> {
+impl<T: Typed> Needed<T> {
     /// Converts to the required field if it has been provided.
     ///
     /// # Note
@@ -91,7 +253,7 @@ impl<T: Data> Needed<T> {
     /// If the required field is present, this function guarantees the returned
     /// `Ok` variant contains `Some(T)`.
     fn get(self) -> Result<Option<T>> {
-        self.0.ok_or(Error::Required(T::ident())).map(Some)
+        self.0.ok_or(Error::Required(T::IDENT)).map(Some)
     }
 }
 
@@ -106,6 +268,10 @@ pub(crate) mod tests {
     use super::*;
     use crate::block::core::{Execution, Locations, Model, Pointer, Registers, Version};
 
+    /// Length of the context, holding the memory buffers.
+    pub const CTX: usize = 0x611f;
+
+    /// Encoded blocks and footer, following [`CTX`] bytes of context.
     #[rustfmt::skip]
     pub const BYTES: &[u8] = &[
         // Bess: Contents
@@ -128,7 +294,7 @@ pub(crate) mod tests {
         0xd0, 0x00, 0x00, 0x00, // head.len
         0x01, 0x00,             // body.core.version.major
         0x01, 0x00,             // body.core.version.minor
-        b'D', b' ', b' ', b' ', // body.core.model
+        b'G', b'D', b' ', b' ', // body.core.model
         0x00, 0x01,             // body.core.reg.pc
         0xb0, 0x01,             // body.core.reg.af
         0x13, 0x00,             // body.core.reg.bc
@@ -138,6 +304,7 @@ pub(crate) mod tests {
         0x01,                   // body.core.reg.ime
         0xe0,                   // body.core.reg.ie
         0x00,                   // body.core.reg.exe
+        0x00,                   // body.core.reg.rsvd
                                 // body.core.reg.mmio
         0xff, 0x00, 0x7e, 0xff, // ... 0xff00
         0xcf, 0x00, 0x00, 0xf8, // ... 0xff04
@@ -172,15 +339,15 @@ pub(crate) mod tests {
         0xff, 0xff, 0xff, 0xff, // ... 0xff78
         0xff, 0xff, 0xff, 0xff, // ... 0xff7c
         0x00, 0x20, 0x00, 0x00, // body.core.mem.wram.len
-        0x00, 0xa0, 0x00, 0x00, // body.core.mem.wram.ptr
+        0x00, 0x00, 0x00, 0x00, // body.core.mem.wram.ptr
         0x00, 0x20, 0x00, 0x00, // body.core.mem.vram.len
-        0x00, 0x80, 0x00, 0x00, // body.core.mem.vram.ptr
+        0x00, 0x20, 0x00, 0x00, // body.core.mem.vram.ptr
         0x00, 0x20, 0x00, 0x00, // body.core.mem.eram.len
-        0x00, 0xc0, 0x00, 0x00, // body.core.mem.eram.ptr
+        0x00, 0x40, 0x00, 0x00, // body.core.mem.eram.ptr
         0xa0, 0x00, 0x00, 0x00, // body.core.mem.oam.len
-        0x00, 0xfe, 0x00, 0x00, // body.core.mem.oam.ptr
+        0x00, 0x60, 0x00, 0x00, // body.core.mem.oam.ptr
         0x7f, 0x00, 0x00, 0x00, // body.core.mem.hram.len
-        0x80, 0xff, 0x00, 0x00, // body.core.mem.hram.ptr
+        0xa0, 0x60, 0x00, 0x00, // body.core.mem.hram.ptr
         0x00, 0x00, 0x00, 0x00, // body.core.mem.bgp.len
         0x00, 0x00, 0x00, 0x00, // body.core.mem.bgp.ptr
         0x00, 0x00, 0x00, 0x00, // body.core.mem.obj.len
@@ -189,14 +356,21 @@ pub(crate) mod tests {
         b'E', b'N', b'D', b' ', // head.ident
         0x00, 0x00, 0x00, 0x00, // head.len
         // Bess: Footer
-        0x00, 0x00, 0x00, 0x00, // end.start
+        0x1f, 0x61, 0x00, 0x00, // end.start
         b'B', b'E', b'S', b'S', // end.magic
     ];
 
-    pub fn setup() -> Bess {
-        let core = Core {
-            version: Version { major: 1, minor: 1 },
-            model: Model(*b"D   "),
+    /// Encodes the context followed by [`BYTES`].
+    pub fn bytes() -> Vec<u8> {
+        let mut buf = vec![0; CTX];
+        buf.extend(BYTES);
+        buf
+    }
+
+    pub fn core() -> Core {
+        Core {
+            version: Version::CURRENT,
+            model: Model(*b"GD  "),
             reg: Registers {
                 pc: 0x0100,
                 af: 0x01b0,
@@ -228,20 +402,23 @@ pub(crate) mod tests {
             },
             #[rustfmt::skip]
             mem: Locations {
-                wram: Pointer { len: 0x2000, ptr: 0xa000, },
-                vram: Pointer { len: 0x2000, ptr: 0x8000, },
-                eram: Pointer { len: 0x2000, ptr: 0xc000, },
-                oam:  Pointer { len: 0x00a0, ptr: 0xfe00, },
-                hram: Pointer { len: 0x007f, ptr: 0xff80, },
+                wram: Pointer { len: 0x2000, ptr: 0x0000, },
+                vram: Pointer { len: 0x2000, ptr: 0x2000, },
+                eram: Pointer { len: 0x2000, ptr: 0x4000, },
+                oam:  Pointer { len: 0x00a0, ptr: 0x6000, },
+                hram: Pointer { len: 0x007f, ptr: 0x60a0, },
                 bgp:  Pointer { len: 0, ptr: 0 },
                 obj:  Pointer { len: 0, ptr: 0 },
             },
-        };
+        }
+    }
+
+    pub fn setup() -> Bess {
         Bess::builder()
             .name("bess")
             .info(Info::new(*b"BESS Testing Rom", 0xabcd))
-            .core(core)
-            .build([])
+            .core(core())
+            .build(vec![0; CTX])
             .unwrap()
     }
 
@@ -249,4 +426,33 @@ pub(crate) mod tests {
     fn builder_works() {
         setup();
     }
+
+    #[test]
+    fn builder_bounds_checked() {
+        let err = Bess::builder().core(core()).build([]).unwrap_err();
+        assert!(matches!(err, Error::Bounds { .. }));
+    }
+
+    #[test]
+    fn builder_version_works() {
+        let Core {
+            model, reg, mem, ..
+        } = core();
+        let test = Core::new(model, reg, mem);
+        let found = Bess::builder().core(test).build(vec![0; CTX]).unwrap();
+        assert_eq!(found.get::<Core>().unwrap().version, Version::CURRENT);
+
+        let mut test = core();
+        test.version.minor = 2;
+        let found = Bess::builder().core(test).build(vec![0; CTX]).unwrap();
+        assert_eq!(found.get::<Core>().unwrap().version.minor, 2);
+
+        let version = Version { major: 1, minor: 3 };
+        let found = Bess::builder()
+            .version(version)
+            .core(core())
+            .build(vec![0; CTX])
+            .unwrap();
+        assert_eq!(found.get::<Core>().unwrap().version, version);
+    }
 }
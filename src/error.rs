@@ -1,7 +1,10 @@
 //! When working with BESS goes wrong.
 
+use std::fmt::{self, Display};
+
 use thiserror::Error;
 
+use crate::block::core::{Model, Region, Version};
 use crate::block::Ident;
 
 /// A specialized [`Result`] type for BESS operations.
@@ -12,16 +15,139 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error("pointer out of bounds: {len:#x} bytes at {ptr:#x}")]
+    Bounds { len: u32, ptr: u32 },
+    #[error("{1} (at {0})")]
+    Context(Context, Box<Error>),
+    #[error("invalid value for `{0}`")]
+    Invalid(&'static str),
+    #[error("block length does not match type: `{ident}` ({len:#x} bytes)")]
+    Length { ident: Ident, len: u32 },
+    #[error("bad magic number: {0:#010x}")]
+    Magic(u32),
+    #[error("invalid model: `{0}`")]
+    Model(Model),
     #[error("{0}")]
     Message(String),
+    #[error("block identifier is already registered: `{0}`")]
+    Registered(Ident),
     #[error("required block is missing: `{0}`")]
     Required(Ident),
+    #[error("buffer size does not match model: `{}` ({len:#x} bytes)", .region.name())]
+    Size { region: Region, len: u32 },
+    #[error("bad footer start: {0:#x}")]
+    Start(u32),
     #[error("buffer is too large")]
     TooLarge,
     #[error("buffer is too short")]
     TooShort,
+    #[error("unknown block: `{0}`")]
+    Unknown(Ident),
     #[error("unsupported type")]
     Unsupported,
+    #[error("unknown `{name}` variant: {idx}")]
+    Variant { name: &'static str, idx: u8 },
+    #[error("incompatible version: {0}")]
+    Version(Version),
+}
+
+impl Error {
+    /// Gets the underlying error, stripping any context.
+    #[must_use]
+    pub fn inner(&self) -> &Self {
+        match self {
+            Self::Context(_, err) => err.inner(),
+            err => err,
+        }
+    }
+
+    /// Gets where this error occurred, if known.
+    #[must_use]
+    pub fn context(&self) -> Option<&Context> {
+        match self {
+            Self::Context(ctx, _) => Some(ctx),
+            _ => None,
+        }
+    }
+
+    /// Attaches the offset where this error occurred.
+    ///
+    /// Errors that already have context are left unchanged, as they were
+    /// located more precisely.
+    #[must_use]
+    pub(crate) fn at(self, offset: usize) -> Self {
+        match self {
+            err @ Self::Context(..) => err,
+            err => Self::Context(
+                Context {
+                    offset,
+                    ..Context::default()
+                },
+                Box::new(err),
+            ),
+        }
+    }
+
+    /// Prepends a path segment to this error's context.
+    #[must_use]
+    pub(crate) fn within(mut self, seg: Segment, offset: usize) -> Self {
+        self = self.at(offset);
+        if let Self::Context(ctx, _) = &mut self {
+            ctx.path.insert(0, seg);
+        }
+        self
+    }
+
+    /// Sets the block within which this error occurred.
+    #[must_use]
+    pub(crate) fn block(mut self, ident: Ident, offset: usize) -> Self {
+        self = self.at(offset);
+        if let Self::Context(ctx, _) = &mut self {
+            ctx.ident = Some(ident);
+        }
+        self
+    }
+}
+
+/// Location of an error within a BESS file.
+#[derive(Debug, Default)]
+pub struct Context {
+    /// Absolute offset within the file.
+    pub offset: usize,
+    /// Identifier of the block being parsed.
+    pub ident: Option<Ident>,
+    /// Path to the field being parsed.
+    pub path: Vec<Segment>,
+}
+
+impl Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "offset {:#x}", self.offset)?;
+        if self.ident.is_none() && self.path.is_empty() {
+            return Ok(());
+        }
+        f.write_str(" in `")?;
+        if let Some(ident) = self.ident {
+            write!(f, "{}", ident.to_string().trim_end())?;
+        }
+        for (idx, seg) in self.path.iter().enumerate() {
+            match seg {
+                Segment::Field(name) if idx == 0 && self.ident.is_none() => f.write_str(name)?,
+                Segment::Field(name) => write!(f, ".{name}")?,
+                Segment::Index(idx) => write!(f, "[{idx}]")?,
+            }
+        }
+        f.write_str("`")
+    }
+}
+
+/// Segment of a field path.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Segment {
+    /// Named struct field.
+    Field(&'static str),
+    /// Sequence element index.
+    Index(usize),
 }
 
 #[cfg(feature = "serde")]
@@ -31,11 +31,21 @@ mod build;
 mod error;
 #[cfg(feature = "serde")]
 mod serde;
+mod valid;
 
-pub use self::block::{Block, Data};
-pub use self::error::{Error, Result};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::mem;
+
+use self::block::core::{Core, Region};
+use self::block::Header;
+pub use self::block::{Block, Data, Typed};
+pub use self::error::{Context, Error, Result, Segment};
+#[cfg(feature = "serde")]
+pub use self::serde::de::BessRef;
 #[cfg(feature = "serde")]
 pub use self::serde::{de, ser};
+pub use self::valid::{Diagnostic, Issue, Severity};
 
 /// BESS document.
 #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
@@ -47,11 +57,82 @@ pub struct Bess {
     blx: Vec<Block>,
     /// Footer for BESS file.
     end: Footer,
+    /// Memory buffers fitted to the model by lenient decoding.
+    ///
+    /// These take the place of the buffers within the context, which are left
+    /// unchanged.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    fit: BTreeMap<Region, Vec<u8>>,
+}
+
+impl Bess {
+    /// Gets the blocks specifying contents.
+    #[must_use]
+    pub fn blocks(&self) -> &[Block] {
+        &self.blx
+    }
+
+    /// Gets the body of the first block of type `T`.
+    #[must_use]
+    pub fn get<T: Data>(&self) -> Option<&T> {
+        self.blx.iter().find_map(Block::get)
+    }
+
+    /// Gets the buffer of a memory region from the context.
+    ///
+    /// Buffers whose size did not match the model when decoded leniently are
+    /// returned as fitted by the fallbacks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `CORE` block is missing, or the region's pointer
+    /// lies beyond the context.
+    pub fn memory(&self, region: Region) -> Result<&[u8]> {
+        if let Some(buf) = self.fit.get(&region) {
+            return Ok(buf);
+        }
+        let core = self.get::<Core>().ok_or(Error::Required(Core::IDENT))?;
+        core.mem.get(region).get(&self.ctx).map_err(|err| {
+            // Locate the pointer within the `CORE` block
+            let offset = self
+                .blx
+                .iter()
+                .take_while(|blk| *blk.ident() != Core::IDENT)
+                .fold(self.end.start as usize, |offset, blk| {
+                    offset + mem::size_of::<Header>() + blk.len() as usize
+                })
+                + mem::size_of::<Header>();
+            err.within(Segment::Field(region.name()), offset + region.offset())
+                .within(Segment::Field("mem"), offset)
+                .block(Core::IDENT, offset)
+        })
+    }
+
+    /// Gets the buffer of a memory region from the context, resized to `len`
+    /// bytes.
+    ///
+    /// As the specification requires, superfluous bytes are ignored, while
+    /// missing bytes are filled with zeros.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `CORE` block is missing, or the region's pointer
+    /// lies beyond the context.
+    pub fn memory_fit(&self, region: Region, len: usize) -> Result<Cow<'_, [u8]>> {
+        let buf = self.memory(region)?;
+        Ok(if let Some(buf) = buf.get(..len) {
+            Cow::Borrowed(buf)
+        } else {
+            let mut buf = buf.to_vec();
+            buf.resize(len, 0);
+            Cow::Owned(buf)
+        })
+    }
 }
 
 /// Footer appended to declare BESS files.
 #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 struct Footer {
     start: u32,
     magic: u32,
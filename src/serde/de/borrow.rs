@@ -0,0 +1,251 @@
+//! Zero-copy views over BESS bytes.
+
+#![allow(clippy::len_without_is_empty)]
+
+use std::mem;
+
+use serde::Deserialize;
+
+use super::{Deserializer, Mode, Registry};
+use crate::block::core::{Core, Pointer};
+use crate::block::info::Info;
+use crate::block::name::Name;
+use crate::block::{Block, Header, Ident};
+use crate::{Bess, Data, Diagnostic, Error, Footer, Result, Typed};
+
+/// Borrowed view over a BESS file.
+///
+/// Unlike [`Bess`], a `BessRef` does not own or copy any of its contents. Block
+/// headers are parsed lazily on access, and buffers are returned as sub-slices
+/// of the underlying bytes. This makes it well suited to scanning many files,
+/// such as over memory-mapped inputs.
+///
+/// Use [`BessRef::to_bess`] to explicitly convert into an owned [`Bess`].
+#[derive(Clone, Copy, Debug)]
+pub struct BessRef<'a> {
+    buf: &'a [u8],
+    end: Footer,
+}
+
+impl<'a> BessRef<'a> {
+    /// Constructs a new `BessRef`, validating only the footer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the footer is invalid.
+    pub fn new(buf: &'a [u8]) -> Result<Self> {
+        // Locate the footer
+        let ftx = buf
+            .len()
+            .checked_sub(mem::size_of::<Footer>())
+            .ok_or(Error::TooShort)?;
+        // Decode the footer
+        let end = Footer::decode_at(&buf[ftx..], ftx)?;
+
+        Ok(Self { buf, end })
+    }
+
+    /// Gets the context.
+    #[must_use]
+    pub fn ctx(&self) -> &'a [u8] {
+        &self.buf[..self.end.start as usize]
+    }
+
+    /// Gets an iterator over the blocks.
+    #[must_use]
+    pub fn blocks(&self) -> Blocks<'a> {
+        let ftx = self.buf.len() - mem::size_of::<Footer>();
+        let buf = &self.buf[self.end.start as usize..ftx];
+        Blocks {
+            de: Deserializer::from_bytes_at(buf, self.end.start as usize),
+        }
+    }
+
+    /// Finds the first block with the specified identifier.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a block preceding it is invalid.
+    pub fn find(&self, ident: Ident) -> Result<Option<BlockRef<'a>>> {
+        for blk in self.blocks() {
+            let blk = blk?;
+            if *blk.ident() == ident {
+                return Ok(Some(blk));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Decodes the body of the first block of type `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the block, or a block preceding it, is invalid.
+    pub fn get<T>(&self) -> Result<Option<T>>
+    where
+        T: Typed + Deserialize<'a>,
+    {
+        self.find(T::IDENT)?.map(|blk| blk.decode()).transpose()
+    }
+
+    /// Gets the name of the originating emulator, if present.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `NAME` block, or a block preceding it, is
+    /// invalid.
+    pub fn name(&self) -> Result<Option<&'a str>> {
+        self.find(Name::IDENT)?
+            .map(|blk| {
+                std::str::from_utf8(blk.body)
+                    .map_err(|_| Error::Invalid("str").block(*blk.ident(), blk.offset))
+            })
+            .transpose()
+    }
+
+    /// Decodes the `INFO` block, if present.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `INFO` block, or a block preceding it, is
+    /// invalid.
+    pub fn info(&self) -> Result<Option<Info>> {
+        self.get()
+    }
+
+    /// Decodes the required `CORE` block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `CORE` block is missing or invalid.
+    pub fn core(&self) -> Result<Core> {
+        self.get()?.ok_or(Error::Required(Core::IDENT))
+    }
+
+    /// Gets the buffer pointed to within the context.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pointer is out of bounds of the context.
+    pub fn memory(&self, ptr: Pointer) -> Result<&'a [u8]> {
+        ptr.get(self.ctx())
+    }
+
+    /// Converts into an owned [`Bess`], decoding every block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any block is invalid.
+    pub fn to_bess(&self) -> Result<Bess> {
+        self.to_bess_with(Mode::Strict, &Registry::new())
+            .map(|(bess, _)| bess)
+    }
+
+    /// Converts into an owned [`Bess`], decoding every block according to the
+    /// specified mode and registry, returning any warnings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any block is invalid.
+    pub fn to_bess_with(&self, mode: Mode, reg: &Registry) -> Result<(Bess, Vec<Diagnostic>)> {
+        let ftx = self.buf.len() - mem::size_of::<Footer>();
+        let start = self.end.start as usize;
+        let mut out = Vec::new();
+        let blx = Block::decode_with(&self.buf[start..ftx], start, mode, reg, &mut out)?;
+        let bess = Bess::from_parts(self.ctx().to_vec(), blx, self.end);
+        Ok((bess, out))
+    }
+}
+
+/// Borrowed view over a block.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockRef<'a> {
+    head: Header,
+    offset: usize,
+    body: &'a [u8],
+}
+
+impl<'a> BlockRef<'a> {
+    /// Gets the block's identifier.
+    #[must_use]
+    pub const fn ident(&self) -> &Ident {
+        self.head.ident()
+    }
+
+    /// Gets the block's length.
+    #[must_use]
+    pub const fn len(&self) -> u32 {
+        self.head.len()
+    }
+
+    /// Gets the block's body.
+    #[must_use]
+    pub fn body(&self) -> &'a [u8] {
+        self.body
+    }
+
+    /// Decodes the block's body as type `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the block is not of type `T`, or its body is
+    /// invalid.
+    pub fn decode<T>(&self) -> Result<T>
+    where
+        T: Typed + Deserialize<'a>,
+    {
+        let ident = *self.ident();
+        let err = |err: Error| err.block(ident, self.offset);
+        // Check the identifier
+        if ident != T::IDENT {
+            return Err(err(Error::Unsupported));
+        }
+        // Deserialize from bytes
+        let mut de = Deserializer::from_bytes_at(self.body, self.offset);
+        let body = T::deserialize(&mut de).map_err(err)?;
+        // Check the length
+        if body.len() != self.len() {
+            return Err(err(Error::Length {
+                ident,
+                len: self.len(),
+            }));
+        }
+        Ok(body)
+    }
+}
+
+/// Iterator over the blocks of a [`BessRef`].
+///
+/// Yields an error upon encountering an invalid header, after which iteration
+/// ends.
+#[derive(Debug)]
+pub struct Blocks<'a> {
+    de: Deserializer<'a>,
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = Result<BlockRef<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.de.input.is_empty() {
+            return None;
+        }
+        let res = (|| {
+            // Read the head
+            let head = Header::deserialize(&mut self.de)?;
+            let ident = *head.ident();
+            // Borrow the body
+            let offset = self.de.offset;
+            let body = self
+                .de
+                .pop(head.len() as usize)
+                .map_err(|err| err.block(ident, offset))?;
+            Ok(BlockRef { head, offset, body })
+        })();
+        if res.is_err() {
+            // Stop after an error
+            self.de.input = &[];
+        }
+        Some(res)
+    }
+}
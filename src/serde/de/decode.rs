@@ -1,54 +1,231 @@
+use std::collections::BTreeMap;
 use std::io::Read;
 use std::mem;
 
 use serde::Deserialize;
 
-use super::Deserializer;
-use crate::block::Header;
-use crate::{Bess, Data, Error, Footer, Result};
+use super::{Deserializer, Mode, Registry};
+use crate::block::core::{Core, Locations, Model, Region, Version};
+use crate::block::raw::Raw;
+use crate::block::sgb::Sgb;
+use crate::block::{Block, Header, Ident};
+use crate::error::Segment;
+use crate::{Bess, Diagnostic, Error, Footer, Issue, Result, MAGIC};
 
 pub trait Decode: Sized {
     fn decode(input: impl Read) -> Result<Self>;
 }
 
 impl Decode for Bess {
-    fn decode(mut input: impl Read) -> Result<Self> {
+    fn decode(input: impl Read) -> Result<Self> {
+        Self::decode_with(input, Mode::Strict, &Registry::new()).map(|(bess, _)| bess)
+    }
+}
+
+impl Bess {
+    /// Decodes according to the specified mode and registry, returning any
+    /// warnings.
+    pub(super) fn decode_with(
+        mut input: impl Read,
+        mode: Mode,
+        reg: &Registry,
+    ) -> Result<(Self, Vec<Diagnostic>)> {
         // Read the entire buffer
         let mut buf = Vec::new();
         input.read_to_end(&mut buf)?;
+        // Locate the footer
+        let ftx = buf
+            .len()
+            .checked_sub(mem::size_of::<Footer>())
+            .ok_or(Error::TooShort)?;
         // Decode the footer
-        let end = {
-            // Extract footer bytes
-            let len = buf.len();
-            let ftx = len - mem::size_of::<Footer>();
-            let buf = buf.get(ftx..).ok_or(Error::TooShort)?;
-            // Deserialize from bytes
-            let mut de = Deserializer::from_bytes(buf);
-            Footer::deserialize(&mut de)?
-        };
+        let end = Footer::decode_at(&buf[ftx..], ftx)?;
         // Decode the context
-        let ctx = buf
-            .get(..end.start as usize)
-            .ok_or(Error::TooShort)?
-            .to_vec();
+        let ctx = buf[..end.start as usize].to_vec();
         // Decode the blocks
-        let blx = {
-            let vec = Vec::new();
-            // Extract blocks bytes
-            let len = buf.len();
-            let ftx = len - mem::size_of::<Footer>();
-            let buf = buf.get(end.start as usize..ftx).ok_or(Error::TooShort)?;
-            // Deserialize from bytes
-            let mut de = Deserializer::from_bytes(buf);
-            while !de.input.is_empty() {
-                // Read the head
-                let head = Header::deserialize(&mut de)?;
-                let blk = todo!();
-                vec.push(blk);
+        let mut out = Vec::new();
+        let blx = Block::decode_with(
+            &buf[end.start as usize..ftx],
+            end.start as usize,
+            mode,
+            reg,
+            &mut out,
+        )?;
+
+        Ok((Bess::from_parts(ctx, blx, end), out))
+    }
+
+    /// Assembles decoded parts, fitting any memory buffers whose size does not
+    /// match the model.
+    ///
+    /// Such buffers are only accepted when lenient, so this has no effect on
+    /// strictly decoded parts.
+    pub(super) fn from_parts(ctx: Vec<u8>, blx: Vec<Block>, end: Footer) -> Self {
+        let mut fit = BTreeMap::new();
+        if let Some(core) = blx.iter().find_map(Block::get::<Core>) {
+            for (region, size) in core.mem.misfits(core.model) {
+                // Pointers were checked when decoding
+                let mut buf = core.mem.get(region).get(&ctx).unwrap_or_default().to_vec();
+                buf.resize(size as usize, 0);
+                fit.insert(region, buf);
+            }
+        }
+        Self { ctx, blx, end, fit }
+    }
+}
+
+impl Footer {
+    /// Decodes a footer located at an absolute offset.
+    ///
+    /// The footer is validated such that its start lies before the footer
+    /// itself.
+    pub(super) fn decode_at(buf: &[u8], ftx: usize) -> Result<Self> {
+        // Deserialize from bytes
+        let mut de = Deserializer::from_bytes_at(buf, ftx);
+        let end = Footer::deserialize(&mut de)?;
+        // Validate the footer
+        if end.magic != MAGIC {
+            return Err(Error::Magic(end.magic).within(Segment::Field("magic"), ftx + 4));
+        }
+        if end.start as usize > ftx {
+            return Err(Error::Start(end.start).within(Segment::Field("start"), ftx));
+        }
+        Ok(end)
+    }
+}
+
+impl Block {
+    /// Decodes every block within a buffer located at an absolute offset
+    /// according to the specified mode and registry, reporting any warnings.
+    pub(super) fn decode_with(
+        buf: &[u8],
+        offset: usize,
+        mode: Mode,
+        reg: &Registry,
+        out: &mut Vec<Diagnostic>,
+    ) -> Result<Vec<Self>> {
+        let lenient = mode == Mode::Lenient;
+        // Blocks follow the context
+        let ctx = offset;
+        let mut vec = Vec::new();
+        // Deserialize from bytes
+        let mut de = Deserializer::from_bytes_at(buf, offset);
+        while !de.input.is_empty() {
+            // Read the head
+            let head = Header::deserialize(&mut de)?;
+            let ident = *head.ident();
+            // Bound the body
+            let offset = de.offset;
+            let mut de = de
+                .take(head.len() as usize)
+                .map_err(|err| err.block(ident, offset))?;
+            // Preserve unknown blocks when lenient
+            if !reg.is_known(ident) {
+                if !lenient {
+                    return Err(Error::Unknown(ident).block(ident, offset));
+                }
+                out.push(Diagnostic::warning(Issue::Unknown(ident)));
             }
-            vec
-        };
+            // Dispatch on the identifier
+            let blk = Block::decode(ident, &mut de, reg).map_err(|err| err.block(ident, offset))?;
+            // Check the length
+            if blk.len() != head.len() {
+                let err = Error::Length {
+                    ident,
+                    len: head.len(),
+                };
+                return Err(err.block(ident, offset));
+            }
+            // Check the version, pointers and buffers
+            if let Some(core) = blk.get::<Core>() {
+                core.version.check_with(mode, out).map_err(|err| {
+                    err.within(Segment::Field("version"), offset)
+                        .block(ident, offset)
+                })?;
+                core.mem.check_len(ctx).map_err(|err| {
+                    err.within(Segment::Field("mem"), offset + Region::Wram.offset())
+                        .block(ident, offset)
+                })?;
+                core.mem
+                    .check_with(core.model, mode, offset, out)
+                    .map_err(|err| err.block(ident, offset))?;
+            }
+            // Check the pointers
+            if let Some(sgb) = blk.get::<Sgb>() {
+                sgb.check_len(ctx).map_err(|err| err.block(ident, offset))?;
+            }
+            vec.push(blk);
+        }
+        Ok(vec)
+    }
+
+    /// Decodes a block body according to its identifier.
+    ///
+    /// Unrecognized identifiers are preserved as raw blocks.
+    fn decode(ident: Ident, de: &mut Deserializer, reg: &Registry) -> Result<Self> {
+        match reg.lookup(ident) {
+            Some(kind) => kind.decode(de),
+            None => Ok(Block::from(Raw::new(ident, de.pop(de.input.len())?))),
+        }
+    }
+}
+
+impl Version {
+    /// Checks that this version is supported according to the specified mode.
+    ///
+    /// Newer minor versions are always accepted, but reported when lenient.
+    fn check_with(self, mode: Mode, out: &mut Vec<Diagnostic>) -> Result<()> {
+        self.check()?;
+        if mode == Mode::Lenient && self > Self::CURRENT {
+            out.push(Diagnostic::warning(Issue::Minor(self.minor)));
+        }
+        Ok(())
+    }
+}
+
+impl Locations {
+    /// Checks that the buffer sizes match the model according to the specified
+    /// mode, where `offset` locates the `CORE` block's body.
+    ///
+    /// Mismatched sizes are rejected when strict. When lenient, they are
+    /// reported, and later fitted by [`Bess::from_parts`].
+    fn check_with(
+        &self,
+        model: Model,
+        mode: Mode,
+        offset: usize,
+        out: &mut Vec<Diagnostic>,
+    ) -> Result<()> {
+        for (region, size) in self.misfits(model) {
+            let len = self.get(region).len;
+            if mode == Mode::Strict {
+                let err = Error::Size { region, len };
+                return Err(err
+                    .within(Segment::Field(region.name()), offset + region.offset())
+                    .within(Segment::Field("mem"), offset + Region::Wram.offset()));
+            }
+            out.push(Diagnostic::warning(if len > size {
+                Issue::Extra {
+                    region,
+                    len: len - size,
+                }
+            } else {
+                Issue::Short {
+                    region,
+                    len: size - len,
+                }
+            }));
+        }
+        Ok(())
+    }
 
-        Ok(Bess { ctx, blx, end })
+    /// Gets the regions whose buffer size does not match the model, along with
+    /// their expected size.
+    fn misfits(&self, model: Model) -> impl Iterator<Item = (Region, u32)> + '_ {
+        let family = model.family().ok();
+        Region::ALL.into_iter().filter_map(move |region| {
+            let size = region.size(family?)?;
+            (self.get(region).len != size).then_some((region, size))
+        })
     }
 }
@@ -4,11 +4,20 @@ use std::mem;
 
 use serde::{de, Deserialize};
 
-use crate::{Bess, Error, Result};
+use crate::error::Segment;
+use crate::{Bess, Diagnostic, Error, Result};
 
+mod borrow;
 mod decode;
+mod read;
+pub(crate) mod registry;
+mod strip;
 
+pub use self::borrow::{BessRef, BlockRef, Blocks};
 use self::decode::Decode;
+pub use self::read::Reader;
+pub use self::registry::Registry;
+pub use self::strip::{strip, strip_file};
 
 impl TryFrom<&[u8]> for Bess {
     type Error = Error;
@@ -25,42 +34,96 @@ impl TryFrom<&[u8]> for Bess {
 ///
 /// Returns an error when the input is invalid.
 pub fn from_bytes(bytes: &[u8]) -> Result<Bess> {
-    let mut de = Deserializer::from_bytes(bytes);
-    let bess = Bess::deserialize(&mut de)?;
-    if de.input.is_empty() {
-        Ok(bess)
-    } else {
-        todo!("trailing characters"); // FIXME
-    }
+    Bess::decode(bytes)
+}
+
+/// Deserializes an instance of a `Bess` from bytes according to the specified
+/// mode and registry, returning any warnings.
+///
+/// # Errors
+///
+/// Returns an error when the input is invalid.
+pub fn from_bytes_with(
+    bytes: &[u8],
+    mode: Mode,
+    reg: &Registry,
+) -> Result<(Bess, Vec<Diagnostic>)> {
+    Bess::decode_with(bytes, mode, reg)
+}
+
+/// Strictness of decoding.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Mode {
+    /// Rejects input that does not match the specification.
+    ///
+    /// Any input requiring a fallback is rejected, including unknown blocks and
+    /// buffers whose size does not match the model.
+    #[default]
+    Strict,
+    /// Applies the best-effort fallbacks described by the specification.
+    ///
+    /// Unknown blocks are preserved as [`Raw`](crate::block::raw::Raw) blocks.
+    /// Buffers whose size does not match the model are fitted, such that
+    /// [`Bess::memory`] ignores extra data and fills short buffers with zeros.
+    /// Each fallback is reported as a warning.
+    ///
+    /// Newer minor versions are accepted by both modes, but only reported when
+    /// lenient.
+    Lenient,
 }
 
+/// Name of tuple structs whose elements are reported as their parent field.
+///
+/// Used by wrappers that only exist to add unnamed bytes, such as reserved
+/// padding, so that their elements don't appear in error paths.
+pub(crate) const TRANSPARENT: &str = "$bess::Transparent";
+
 /// A structure that deserializes BESS into Rust structures.
 #[derive(Debug)]
 struct Deserializer<'de> {
     input: &'de [u8],
+    /// Absolute offset of the input.
+    offset: usize,
 }
 
 impl<'de> Deserializer<'de> {
     /// Constructs a `Deserializer` from a byte array.
     #[must_use]
     pub fn from_bytes(input: &'de [u8]) -> Self {
-        Self { input }
+        Self::from_bytes_at(input, 0)
+    }
+
+    /// Constructs a `Deserializer` from a byte array located at an absolute
+    /// offset within a file.
+    #[must_use]
+    pub fn from_bytes_at(input: &'de [u8], offset: usize) -> Self {
+        Self { input, offset }
+    }
+
+    /// Splits off a sub-deserializer bounded to the next `len` bytes.
+    ///
+    /// The parent deserializer advances past these bytes, regardless of how
+    /// many of them are consumed by the sub-deserializer.
+    fn take(&mut self, len: usize) -> Result<Deserializer<'de>> {
+        let offset = self.offset;
+        self.pop(len)
+            .map(|input| Deserializer::from_bytes_at(input, offset))
     }
 
     /// Pops a slice off the front of the input buffer.
-    fn pop(&mut self, len: usize) -> &[u8] {
-        let pop = &self.input[..len];
-        self.input = &self.input[len..];
-        pop
+    fn pop(&mut self, len: usize) -> Result<&'de [u8]> {
+        if len > self.input.len() {
+            return Err(Error::TooShort);
+        }
+        let (pop, rem) = self.input.split_at(len);
+        self.input = rem;
+        self.offset += len;
+        Ok(pop)
     }
 
     /// Pops an array reference off the front of the input buffer.
-    fn pop_ref<const N: usize>(&mut self) -> &[u8; N] {
-        assert!(N <= self.input.len());
-        let (pop, rem) = self.input.split_at(N);
-        let pop = pop.try_into().unwrap();
-        self.input = rem;
-        pop
+    fn pop_ref<const N: usize>(&mut self) -> Result<&'de [u8; N]> {
+        self.pop(N)?.try_into().map_err(|_| Error::TooShort)
     }
 }
 
@@ -78,11 +141,11 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        let bytes = self.pop_ref::<{ mem::size_of::<u8>() }>();
+        let bytes = self.pop_ref::<{ mem::size_of::<u8>() }>()?;
         let value = match u8::from_le_bytes(*bytes) {
             0b0 => false,
             0b1 => true,
-            _ => panic!(),
+            _ => return Err(Error::Invalid("bool")),
         };
         visitor.visit_bool(value)
     }
@@ -91,70 +154,70 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_i8(i8::from_le_bytes(*self.pop_ref()))
+        visitor.visit_i8(i8::from_le_bytes(*self.pop_ref()?))
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_i16(i16::from_le_bytes(*self.pop_ref()))
+        visitor.visit_i16(i16::from_le_bytes(*self.pop_ref()?))
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_i32(i32::from_le_bytes(*self.pop_ref()))
+        visitor.visit_i32(i32::from_le_bytes(*self.pop_ref()?))
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_i64(i64::from_le_bytes(*self.pop_ref()))
+        visitor.visit_i64(i64::from_le_bytes(*self.pop_ref()?))
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_u8(u8::from_le_bytes(*self.pop_ref()))
+        visitor.visit_u8(u8::from_le_bytes(*self.pop_ref()?))
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_u16(u16::from_le_bytes(*self.pop_ref()))
+        visitor.visit_u16(u16::from_le_bytes(*self.pop_ref()?))
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_u32(u32::from_le_bytes(*self.pop_ref()))
+        visitor.visit_u32(u32::from_le_bytes(*self.pop_ref()?))
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_u64(u64::from_le_bytes(*self.pop_ref()))
+        visitor.visit_u64(u64::from_le_bytes(*self.pop_ref()?))
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_f32(f32::from_le_bytes(*self.pop_ref()))
+        visitor.visit_f32(f32::from_le_bytes(*self.pop_ref()?))
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_f64(f64::from_le_bytes(*self.pop_ref()))
+        visitor.visit_f64(f64::from_le_bytes(*self.pop_ref()?))
     }
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
@@ -168,8 +231,9 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        let bytes = self.input;
-        let str = std::str::from_utf8(bytes).unwrap();
+        // Strings are unprefixed, so they span the remaining input
+        let bytes = self.pop(self.input.len())?;
+        let str = std::str::from_utf8(bytes).map_err(|_| Error::Invalid("str"))?;
         visitor.visit_borrowed_str(str)
     }
 
@@ -184,8 +248,8 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        let bytes = self.input;
-        visitor.visit_borrowed_bytes(bytes)
+        // Bytes are unprefixed, so they span the remaining input
+        visitor.visit_seq(Access::new(self, None, &[]))
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
@@ -227,15 +291,15 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_tuple(self.input.len(), visitor)
+        // Sequences are unprefixed, so they span the remaining input
+        visitor.visit_seq(Access::new(self, None, &[]))
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let mut de = Deserializer::from_bytes(&self.input[..len]);
-        visitor.visit_seq(&mut de)
+        visitor.visit_seq(Access::new(self, Some(len), &[]))
     }
 
     fn deserialize_tuple_struct<V>(
@@ -247,6 +311,11 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
+        if name == TRANSPARENT {
+            let mut access = Access::new(self, Some(len), &[]);
+            access.transparent = true;
+            return visitor.visit_seq(access);
+        }
         self.deserialize_tuple(len, visitor)
     }
 
@@ -254,7 +323,7 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_tuple(self.input.len(), visitor)
+        self.deserialize_seq(visitor)
     }
 
     fn deserialize_struct<V>(
@@ -266,7 +335,7 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_tuple(fields.len(), visitor)
+        visitor.visit_seq(Access::new(self, Some(fields.len()), fields))
     }
 
     fn deserialize_enum<V>(
@@ -278,6 +347,11 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
+        // Reject unknown variants before visiting
+        let idx = *self.input.first().ok_or(Error::TooShort)?;
+        if usize::from(idx) >= variants.len() {
+            return Err(Error::Variant { name, idx });
+        }
         visitor.visit_enum(self)
     }
 
@@ -304,20 +378,76 @@ impl<'de> de::EnumAccess<'de> for &mut Deserializer<'de> {
     where
         V: de::DeserializeSeed<'de>,
     {
-        let idx = u32::deserialize(&mut *self)?;
+        let idx = u32::from(u8::deserialize(&mut *self)?);
         let val = seed.deserialize(de::IntoDeserializer::<Error>::into_deserializer(idx))?;
         Ok((val, self))
     }
 }
 
-impl<'de> de::SeqAccess<'de> for &mut Deserializer<'de> {
+/// Sequence access over elements of a `Deserializer`.
+///
+/// When no length is known, elements are yielded until the input is
+/// exhausted. Errors are annotated with the element's field name, if provided,
+/// or its index otherwise.
+struct Access<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    len: Option<usize>,
+    idx: usize,
+    fields: &'static [&'static str],
+    /// Whether errors are reported as within the parent, without a segment.
+    transparent: bool,
+}
+
+impl<'a, 'de> Access<'a, 'de> {
+    /// Constructs a new `Access`.
+    fn new(
+        de: &'a mut Deserializer<'de>,
+        len: Option<usize>,
+        fields: &'static [&'static str],
+    ) -> Self {
+        Self {
+            de,
+            len,
+            idx: 0,
+            fields,
+            transparent: false,
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for Access<'_, 'de> {
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
     where
         T: de::DeserializeSeed<'de>,
     {
-        todo!()
+        match &mut self.len {
+            Some(0) => return Ok(None),
+            Some(len) => *len -= 1,
+            None if self.de.input.is_empty() => return Ok(None),
+            None => (),
+        }
+        // Locate this element
+        let seg = self
+            .fields
+            .get(self.idx)
+            .map_or(Segment::Index(self.idx), |field| Segment::Field(field));
+        let offset = self.de.offset;
+        self.idx += 1;
+        // Deserialize this element
+        let transparent = self.transparent;
+        seed.deserialize(&mut *self.de).map(Some).map_err(|err| {
+            if transparent {
+                err.at(offset)
+            } else {
+                err.within(seg, offset)
+            }
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.len
     }
 }
 
@@ -352,8 +482,20 @@ impl<'de> de::VariantAccess<'de> for &mut Deserializer<'de> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use super::*;
-    use crate::build::tests::{setup, BYTES};
+    use crate::block::core::{Core, Pointer, Region};
+    use crate::block::end::End;
+    use crate::block::huc3::{Counter, Huc3};
+    use crate::block::info::Info;
+    use crate::block::mbc::{Mbc, Write};
+    use crate::block::mbc7::Flags;
+    use crate::block::raw::Raw;
+    use crate::block::sgb::{Multiplayer, Sgb};
+    use crate::block::{Block, Ident};
+    use crate::build::tests::{bytes, core, setup, BYTES, CTX};
+    use crate::{Data, Diagnostic, Footer, Issue, Typed};
 
     #[test]
     fn int_deserialize_works() {
@@ -382,13 +524,13 @@ mod tests {
         #[derive(Debug, Deserialize, PartialEq)]
         struct Test {
             int: u32,
-            seq: Vec<String>,
+            str: String,
         }
 
         let test = &[0x01, 0x00, 0x00, 0x00, b'a', b'b'];
         let expect = Test {
             int: 1,
-            seq: vec!["a".to_string(), "b".to_string()],
+            str: "ab".to_string(),
         };
 
         let mut de = Deserializer::from_bytes(test);
@@ -397,11 +539,817 @@ mod tests {
         assert_eq!(found, expect);
     }
 
+    #[test]
+    fn bounded_deserialize_works() {
+        let test = &[
+            b'a', b'b', // str
+            0x00, 0x01, 0x02, 0x10, 0x11, 0x12, // seq
+            0x34, 0x12, // int
+        ];
+
+        let mut de = Deserializer::from_bytes(test);
+        let str = String::deserialize(&mut de.take(2).unwrap()).unwrap();
+        let seq = Vec::<(u16, u8)>::deserialize(&mut de.take(6).unwrap()).unwrap();
+        let int = u16::deserialize(&mut de).unwrap();
+
+        assert_eq!(str, "ab");
+        assert_eq!(seq, [(0x0100, 0x02), (0x1110, 0x12)]);
+        assert_eq!(int, 0x1234);
+        assert!(de.input.is_empty());
+    }
+
+    #[test]
+    fn invalid_deserialize_fails() {
+        let test = &[0x02];
+        let mut de = Deserializer::from_bytes(test);
+        assert!(matches!(bool::deserialize(&mut de), Err(Error::Invalid(_))));
+
+        let test = &[0xff, 0xfe];
+        let mut de = Deserializer::from_bytes(test);
+        assert!(matches!(
+            String::deserialize(&mut de),
+            Err(Error::Invalid(_))
+        ));
+
+        let test = &[0x34];
+        let mut de = Deserializer::from_bytes(test);
+        assert!(matches!(u16::deserialize(&mut de), Err(Error::TooShort)));
+    }
+
+    #[test]
+    fn truncated_decode_fails() {
+        #[rustfmt::skip]
+        let tests: &[&[u8]] = &[
+            // Missing footer
+            b"BESS",
+            // Truncated header
+            &[
+                b'E', b'N', b'D', b' ', // head.ident
+                0x00, 0x00,             // head.len
+                0x00, 0x00, 0x00, 0x00, // end.start
+                b'B', b'E', b'S', b'S', // end.magic
+            ],
+            // Truncated body
+            &[
+                b'N', b'A', b'M', b'E', // head.ident
+                0x08, 0x00, 0x00, 0x00, // head.len
+                b'b', b'e', b's', b's', // body.name
+                0x00, 0x00, 0x00, 0x00, // end.start
+                b'B', b'E', b'S', b'S', // end.magic
+            ],
+        ];
+        for test in tests {
+            let err = Bess::try_from(*test).unwrap_err();
+            assert!(matches!(err.inner(), Error::TooShort));
+        }
+
+        // Truncating anywhere must not panic
+        for len in 0..BYTES.len() {
+            let _ = Bess::try_from(&BYTES[..len]);
+        }
+    }
+
+    #[test]
+    fn corrupted_decode_fails() {
+        // Bad magic
+        let mut test = BYTES.to_vec();
+        *test.last_mut().unwrap() = b'X';
+        let err = Bess::try_from(&test[..]).unwrap_err();
+        assert!(matches!(err.inner(), Error::Magic(_)));
+        assert_eq!(err.context().unwrap().offset, BYTES.len() - 4);
+
+        // Bad footer start
+        #[rustfmt::skip]
+        let test = &[
+            0xff, 0x00, 0x00, 0x00, // end.start
+            b'B', b'E', b'S', b'S', // end.magic
+        ];
+        let err = Bess::try_from(&test[..]).unwrap_err();
+        assert!(matches!(err.inner(), Error::Start(0xff)));
+
+        // Unknown execution state
+        let mut test = bytes();
+        test[CTX + 0x44] = 0x03;
+        let err = Bess::try_from(&test[..]).unwrap_err();
+        assert!(matches!(
+            err.inner(),
+            Error::Variant {
+                name: "Execution",
+                idx: 3
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "unknown `Execution` variant: 3 (at offset 0x6163 in `CORE.reg.exe`)"
+        );
+
+        // Out of bounds pointer
+        let mut test = bytes();
+        test[CTX + 230] = 0x80;
+        let err = Bess::try_from(&test[..]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "pointer out of bounds: 0x80 bytes at 0x60a0 (at offset 0x61e5 in `CORE.mem`)"
+        );
+
+        // Short block
+        #[rustfmt::skip]
+        let test = &[
+            b'C', b'O', b'R', b'E', // head.ident
+            0x10, 0x00, 0x00, 0x00, // head.len
+            0x01, 0x00,             // body.core.version.major
+            0x01, 0x00,             // body.core.version.minor
+            b'G', b'D', b' ', b' ', // body.core.model
+            0x00, 0x01,             // body.core.reg.pc
+            0xb0, 0x01,             // body.core.reg.af
+            0x13, 0x00,             // body.core.reg.bc
+            0xd8, 0x00,             // body.core.reg.de
+            0x00, 0x00, 0x00, 0x00, // end.start
+            b'B', b'E', b'S', b'S', // end.magic
+        ];
+        let err = Bess::try_from(&test[..]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "buffer is too short (at offset 0x18 in `CORE.reg.hl`)"
+        );
+
+        // Long block
+        #[rustfmt::skip]
+        let test = &[
+            b'E', b'N', b'D', b' ', // head.ident
+            0x01, 0x00, 0x00, 0x00, // head.len
+            0x00,                   // body
+            0x00, 0x00, 0x00, 0x00, // end.start
+            b'B', b'E', b'S', b'S', // end.magic
+        ];
+        let err = Bess::try_from(&test[..]).unwrap_err();
+        assert!(matches!(err.inner(), Error::Length { len: 1, .. }));
+    }
+
     #[test]
     fn builder_serialize_works() {
-        let test = Bess::try_from(BYTES).unwrap();
+        let test = Bess::try_from(&bytes()[..]).unwrap();
         let expect = setup();
 
         assert_eq!(test.to_bytes(), expect.to_bytes());
     }
+
+    #[test]
+    fn mbc_decode_works() {
+        #[rustfmt::skip]
+        let test = &[
+            b'M', b'B', b'C', b' ', // head.ident
+            0x06, 0x00, 0x00, 0x00, // head.len
+            0x00, 0x00, 0x0a,       // body.mbc[0]
+            0x00, 0x20, 0x05,       // body.mbc[1]
+            b'E', b'N', b'D', b' ', // head.ident
+            0x00, 0x00, 0x00, 0x00, // head.len
+            0x00, 0x00, 0x00, 0x00, // end.start
+            b'B', b'E', b'S', b'S', // end.magic
+        ];
+        let found = Bess::try_from(&test[..]).unwrap();
+        let expect = Mbc::new([
+            Write {
+                addr: 0x0000,
+                data: 0x0a,
+            },
+            Write {
+                addr: 0x2000,
+                data: 0x05,
+            },
+        ])
+        .unwrap();
+
+        assert_eq!(format!("{:?}", found.blx[0].body()), format!("{expect:?}"));
+        assert_eq!(found.to_bytes(), test);
+
+        // Partial writes are rejected
+        let mut test = test.to_vec();
+        test[4] = 0x05;
+        test.remove(13);
+        let err = Bess::try_from(&test[..]).unwrap_err();
+        assert!(matches!(err.inner(), Error::TooShort));
+    }
+
+    #[test]
+    fn rtc_decode_works() {
+        #[rustfmt::skip]
+        let test = &[
+            b'R', b'T', b'C', b' ', // head.ident
+            0x30, 0x00, 0x00, 0x00, // head.len
+            0x3b, 0x00, 0x00, 0x00, // body.rtc.current.sec
+            0x1e, 0x00, 0x00, 0x00, // body.rtc.current.min
+            0x17, 0x00, 0x00, 0x00, // body.rtc.current.hour
+            0xff, 0x00, 0x00, 0x00, // body.rtc.current.days
+            0x81, 0x00, 0x00, 0x00, // body.rtc.current.high
+            0x3a, 0x00, 0x00, 0x00, // body.rtc.latched.sec
+            0x1e, 0x00, 0x00, 0x00, // body.rtc.latched.min
+            0x17, 0x00, 0x00, 0x00, // body.rtc.latched.hour
+            0xff, 0x00, 0x00, 0x00, // body.rtc.latched.days
+            0x81, 0x00, 0x00, 0x00, // body.rtc.latched.high
+            0x00, 0x2f, 0x5e, 0x65, // body.rtc.timestamp
+            0x00, 0x00, 0x00, 0x00, // ...
+            b'E', b'N', b'D', b' ', // head.ident
+            0x00, 0x00, 0x00, 0x00, // head.len
+            0x00, 0x00, 0x00, 0x00, // end.start
+            b'B', b'E', b'S', b'S', // end.magic
+        ];
+        let found = Bess::try_from(&test[..]).unwrap();
+
+        assert_eq!(found.blx[0].ident(), &Ident::new(*b"RTC "));
+        assert_eq!(found.to_bytes(), test);
+    }
+
+    #[test]
+    fn huc3_decode_works() {
+        #[rustfmt::skip]
+        let test = &[
+            b'H', b'U', b'C', b'3', // head.ident
+            0x11, 0x00, 0x00, 0x00, // head.len
+            0x00, 0x2f, 0x5e, 0x65, // body.huc3.timestamp
+            0x00, 0x00, 0x00, 0x00, // ...
+            0x3c, 0x00,             // body.huc3.clock.minutes
+            0x02, 0x00,             // body.huc3.clock.days
+            0x78, 0x00,             // body.huc3.alarm.minutes
+            0x03, 0x00,             // body.huc3.alarm.days
+            0x01,                   // body.huc3.enabled
+            b'E', b'N', b'D', b' ', // head.ident
+            0x00, 0x00, 0x00, 0x00, // head.len
+            0x00, 0x00, 0x00, 0x00, // end.start
+            b'B', b'E', b'S', b'S', // end.magic
+        ];
+        let found = Bess::try_from(&test[..]).unwrap();
+        let expect = Huc3 {
+            timestamp: 0x655e_2f00,
+            clock: Counter {
+                minutes: 60,
+                days: 2,
+            },
+            alarm: Counter {
+                minutes: 120,
+                days: 3,
+            },
+            enabled: true,
+        };
+
+        assert_eq!(format!("{:?}", found.blx[0].body()), format!("{expect:?}"));
+        assert_eq!(found.to_bytes(), test);
+        assert_eq!(
+            Bess::builder()
+                .core(core())
+                .huc3(expect)
+                .build(vec![0; CTX])
+                .unwrap()
+                .blx[1]
+                .len(),
+            0x11
+        );
+    }
+
+    #[test]
+    fn tpp1_decode_works() {
+        #[rustfmt::skip]
+        let test = &[
+            b'T', b'P', b'P', b'1', // head.ident
+            0x11, 0x00, 0x00, 0x00, // head.len
+            0x00, 0x2f, 0x5e, 0x65, // body.tpp1.timestamp
+            0x00, 0x00, 0x00, 0x00, // ...
+            0x01, 0x42, 0x1e, 0x3b, // body.tpp1.current
+            0x01, 0x42, 0x1e, 0x3a, // body.tpp1.latched
+            0x04,                   // body.tpp1.mr4
+            b'E', b'N', b'D', b' ', // head.ident
+            0x00, 0x00, 0x00, 0x00, // head.len
+            0x00, 0x00, 0x00, 0x00, // end.start
+            b'B', b'E', b'S', b'S', // end.magic
+        ];
+        let found = Bess::try_from(&test[..]).unwrap();
+
+        assert_eq!(found.blx[0].ident(), &Ident::new(*b"TPP1"));
+        assert_eq!(found.to_bytes(), test);
+
+        // Length must be exact
+        let mut long = test.to_vec();
+        long[4] = 0x12;
+        long.insert(0x19, 0x00);
+        let err = Bess::try_from(&long[..]).unwrap_err();
+        assert!(matches!(err.inner(), Error::Length { len: 0x12, .. }));
+        let mut short = test.to_vec();
+        short[4] = 0x10;
+        short.remove(0x18);
+        let err = Bess::try_from(&short[..]).unwrap_err();
+        assert!(matches!(err.inner(), Error::TooShort));
+    }
+
+    #[test]
+    fn mbc7_decode_works() {
+        #[rustfmt::skip]
+        let test = &[
+            b'M', b'B', b'C', b'7', // head.ident
+            0x0a, 0x00, 0x00, 0x00, // head.len
+            0x31,                   // body.mbc7.flags
+            0x05,                   // body.mbc7.argc
+            0x80, 0x02,             // body.mbc7.command
+            0x00, 0x00,             // body.mbc7.read
+            0xf0, 0x81,             // body.mbc7.x
+            0x10, 0x7e,             // body.mbc7.y
+            b'E', b'N', b'D', b' ', // head.ident
+            0x00, 0x00, 0x00, 0x00, // head.len
+            0x00, 0x00, 0x00, 0x00, // end.start
+            b'B', b'E', b'S', b'S', // end.magic
+        ];
+        let found = Bess::try_from(&test[..]).unwrap();
+
+        assert_eq!(found.blx[0].ident(), &Ident::new(*b"MBC7"));
+        assert_eq!(found.to_bytes(), test);
+
+        let flags = Flags(0x31);
+        assert!(flags.latch_ready());
+        assert!(!flags.eeprom_do());
+        assert!(flags.eeprom_cs());
+        assert!(flags.eeprom_we());
+    }
+
+    #[test]
+    fn sgb_decode_works() {
+        #[rustfmt::skip]
+        let sgb = || Sgb {
+            border_tiles:    Pointer { len: 0x2000, ptr: 0x0000 },
+            border_tilemap:  Pointer { len: 0x0800, ptr: 0x2000 },
+            border_palettes: Pointer { len: 0x0080, ptr: 0x2800 },
+            active_palettes: Pointer { len: 0x0020, ptr: 0x2880 },
+            ram_palettes:    Pointer { len: 0x1000, ptr: 0x28a0 },
+            attribute_map:   Pointer { len: 0x0168, ptr: 0x38a0 },
+            attribute_files: Pointer { len: 0x0fe0, ptr: 0x3a08 },
+            multiplayer: Multiplayer::new(2, 1),
+        };
+        let ctx = vec![0; CTX];
+        let test = Bess::builder()
+            .core(core())
+            .sgb(sgb())
+            .build(ctx.clone())
+            .unwrap()
+            .to_bytes();
+        let found = Bess::try_from(&test[..]).unwrap();
+
+        assert_eq!(found.blx[1].ident(), &Ident::new(*b"SGB "));
+        assert_eq!(found.blx[1].len(), 0x39);
+        assert_eq!(found.to_bytes(), test);
+
+        // Pointers must be within the context
+        assert!(sgb().check(&ctx).is_ok());
+        assert!(matches!(
+            sgb().check(&ctx[..0x4000]),
+            Err(Error::Bounds {
+                len: 0x0fe0,
+                ptr: 0x3a08
+            })
+        ));
+        let far = || Sgb {
+            attribute_files: Pointer {
+                len: 0x0fe0,
+                ptr: 0x6000,
+            },
+            ..sgb()
+        };
+        let err = Bess::builder()
+            .core(core())
+            .sgb(far())
+            .build(ctx.clone())
+            .unwrap_err();
+        assert!(matches!(err, Error::Bounds { ptr: 0x6000, .. }));
+        let test = Bess {
+            blx: vec![core().into(), far().into(), End.into()],
+            end: Footer::new(0x611f),
+            ctx,
+            fit: BTreeMap::new(),
+        }
+        .to_bytes();
+        let err = Bess::try_from(&test[..]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "pointer out of bounds: 0xfe0 bytes at 0x6000 (at offset 0x61ff in `SGB`)"
+        );
+    }
+
+    #[test]
+    fn raw_decode_works() {
+        #[rustfmt::skip]
+        let test = &[
+            b'X', b'F', b'O', b'O', // head.ident
+            0x03, 0x00, 0x00, 0x00, // head.len
+            0x01, 0x02, 0x03,       // body.raw
+            b'E', b'N', b'D', b' ', // head.ident
+            0x00, 0x00, 0x00, 0x00, // head.len
+            0x00, 0x00, 0x00, 0x00, // end.start
+            b'B', b'E', b'S', b'S', // end.magic
+        ];
+        let err = Bess::try_from(&test[..]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown block: `XFOO` (at offset 0x8 in `XFOO`)"
+        );
+        let (found, _) = from_bytes_with(test, Mode::Lenient, &Registry::new()).unwrap();
+
+        assert_eq!(found.blx[0].ident(), &Ident::new(*b"XFOO"));
+        assert_eq!(found.blx[0].len(), 3);
+        assert_eq!(found.to_bytes(), test);
+    }
+
+    #[test]
+    fn registry_decode_works() {
+        #[derive(Debug, Deserialize, serde::Serialize)]
+        struct Xbar(u16);
+
+        #[typetag::serde]
+        impl Data for Xbar {
+            fn ident(&self) -> Ident {
+                Self::IDENT
+            }
+
+            fn len(&self) -> u32 {
+                2
+            }
+        }
+
+        impl Typed for Xbar {
+            const IDENT: Ident = Ident::new(*b"XBAR");
+        }
+
+        #[derive(Debug, Deserialize, serde::Serialize)]
+        struct Xcore;
+
+        #[typetag::serde]
+        impl Data for Xcore {
+            fn ident(&self) -> Ident {
+                Self::IDENT
+            }
+
+            fn len(&self) -> u32 {
+                0
+            }
+        }
+
+        impl Typed for Xcore {
+            const IDENT: Ident = Ident::new(*b"CORE");
+        }
+
+        let mut reg = Registry::new();
+        assert!(!reg.is_known(Xbar::IDENT));
+        reg.register::<Xbar>().unwrap();
+        reg.register::<Xbar>().unwrap();
+        assert!(reg.is_known(Xbar::IDENT));
+        assert!(!Registry::new().is_known(Xbar::IDENT));
+        assert!(matches!(reg.register::<Xcore>(), Err(Error::Registered(_))));
+
+        #[rustfmt::skip]
+        let test = &[
+            b'X', b'B', b'A', b'R', // head.ident
+            0x03, 0x00, 0x00, 0x00, // head.len
+            0x34, 0x12, 0x00,       // body.xbar
+            b'E', b'N', b'D', b' ', // head.ident
+            0x00, 0x00, 0x00, 0x00, // head.len
+            0x00, 0x00, 0x00, 0x00, // end.start
+            b'B', b'E', b'S', b'S', // end.magic
+        ];
+        let err = from_bytes_with(test, Mode::Strict, &reg).unwrap_err();
+        assert!(matches!(err.inner(), Error::Length { len: 3, .. }));
+
+        let test = Bess::builder()
+            .core(core())
+            .block(Xbar(0x1234))
+            .build(vec![0; CTX])
+            .unwrap()
+            .to_bytes();
+        let (found, _) = from_bytes_with(&test, Mode::Strict, &reg).unwrap();
+        assert_eq!(format!("{:?}", found.blx[1].body()), "Xbar(4660)");
+        let (found, _) = from_bytes_with(&test, Mode::Lenient, &Registry::new()).unwrap();
+        assert!(found.blx[1].get::<Raw>().is_some());
+
+        let err = Bess::builder()
+            .core(core())
+            .block(Xcore)
+            .build(vec![0; CTX])
+            .unwrap_err();
+        assert!(matches!(err, Error::Registered(_)));
+
+        let err = Bess::builder()
+            .core(core())
+            .block(Raw::new(Core::IDENT, [0; 4]))
+            .build(vec![0; CTX])
+            .unwrap_err();
+        assert!(matches!(err, Error::Registered(_)));
+
+        let err = Bess::builder()
+            .core(core())
+            .block(crate::block::end::End)
+            .build(vec![0; CTX])
+            .unwrap_err();
+        assert!(matches!(err, Error::Registered(_)));
+
+        let err = Bess::builder()
+            .core(core())
+            .block(Raw::new(Mbc::IDENT, []))
+            .build(vec![0; CTX])
+            .unwrap_err();
+        assert!(matches!(err, Error::Registered(_)));
+
+        let test = Bess::builder()
+            .core(core())
+            .block(Raw::new(Xbar::IDENT, [0; 2]));
+        assert!(test.registry(&reg).build(vec![0; CTX]).is_err());
+
+        let test = Bess::builder()
+            .core(core())
+            .block(Xbar(0x1234))
+            .build(vec![0; CTX])
+            .unwrap();
+        let unknown = |found: Vec<Diagnostic>| {
+            found
+                .iter()
+                .any(|diag| matches!(diag.issue, Issue::Unknown(_)))
+        };
+        assert!(unknown(test.validate()));
+        assert!(!unknown(test.validate_with(&reg)));
+    }
+
+    #[test]
+    fn save_decode_works() {
+        let test = include_bytes!("../../../data/saves/tetris.sav");
+        let found = Bess::try_from(&test[..]).unwrap();
+
+        assert_eq!(found.ctx.len(), 0xc490);
+        assert_eq!(
+            found.blx.iter().map(Block::ident).collect::<Vec<_>>(),
+            [b"NAME", b"INFO", b"CORE", b"XOAM", b"END "]
+                .map(|id| Ident::new(*id))
+                .iter()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(found.to_bytes(), test);
+    }
+
+    #[test]
+    fn reader_works() {
+        /// Reader which counts the number of bytes read.
+        struct Tally<R>(R, usize);
+
+        impl<R: std::io::Read> std::io::Read for Tally<R> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let len = self.0.read(buf)?;
+                self.1 += len;
+                Ok(len)
+            }
+        }
+
+        impl<R: std::io::Seek> std::io::Seek for Tally<R> {
+            fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+                self.0.seek(pos)
+            }
+        }
+
+        let test = include_bytes!("../../../data/saves/tetris.sav");
+        let mut found = Reader::new(Tally(std::io::Cursor::new(test), 0)).unwrap();
+
+        // Only the trailer is read
+        let core = found.get::<Core>().unwrap();
+        let vram = core.mem.vram;
+        assert_eq!(found.blocks().len(), 5);
+        assert_eq!(found.get_ref().1, test.len() - 0xc490);
+
+        // Buffers are read on demand
+        let buf = found.read(vram).unwrap();
+        assert_eq!(buf, test[vram.ptr as usize..][..vram.len as usize]);
+        assert!(matches!(
+            found.read(Pointer {
+                len: 0x10,
+                ptr: 0xc488
+            }),
+            Err(Error::Bounds { .. })
+        ));
+
+        // Converting reads the context
+        let bess = found.into_bess().unwrap();
+        assert_eq!(bess.to_bytes(), test);
+    }
+
+    #[test]
+    fn borrow_works() {
+        let test = include_bytes!("../../../data/saves/tetris.sav");
+        let found = BessRef::new(test).unwrap();
+
+        // Headers are parsed lazily
+        assert_eq!(
+            found
+                .blocks()
+                .map(|blk| *blk.unwrap().ident())
+                .collect::<Vec<_>>(),
+            [b"NAME", b"INFO", b"CORE", b"XOAM", b"END "].map(|id| Ident::new(*id))
+        );
+        assert_eq!(found.name().unwrap(), Some("SameBoy v0.15.8"));
+        assert_eq!(
+            found.info().unwrap().unwrap().title(),
+            b"TETRIS\0\0\0\0\0\0\0\0\0\0"
+        );
+
+        // Buffers are borrowed from the input
+        let vram = found.core().unwrap().mem.vram;
+        let buf = found.memory(vram).unwrap();
+        assert_eq!(buf.as_ptr(), test[vram.ptr as usize..].as_ptr());
+        assert_eq!(buf.len(), vram.len as usize);
+        assert!(matches!(
+            found.memory(Pointer {
+                len: 0x10,
+                ptr: 0xc488
+            }),
+            Err(Error::Bounds { .. })
+        ));
+
+        // Mismatched types are rejected
+        let blk = found.find(Core::IDENT).unwrap().unwrap();
+        assert!(matches!(
+            blk.decode::<Info>().unwrap_err().inner(),
+            Error::Unsupported
+        ));
+
+        // Converting decodes every block
+        let bess = found.to_bess().unwrap();
+        assert_eq!(bess.to_bytes(), test);
+    }
+
+    #[test]
+    fn strip_works() {
+        let test = include_bytes!("../../../data/saves/tetris.sav");
+        let native = &test[..0xc490];
+
+        // Strip in memory
+        assert_eq!(strip(test).unwrap(), native);
+        assert!(matches!(
+            strip(native).unwrap_err().inner(),
+            Error::Magic(_)
+        ));
+
+        // Strip within a file
+        let path = std::env::temp_dir().join(format!("bess-{}.strip", std::process::id()));
+        std::fs::write(&path, test).unwrap();
+        let file = std::fs::File::options()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        strip_file(&file).unwrap();
+        drop(file);
+        let found = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(found, native);
+    }
+
+    #[test]
+    fn memory_works() {
+        let test = include_bytes!("../../../data/saves/tetris.sav");
+        let found = Bess::try_from(&test[..]).unwrap();
+        let vram = found.get::<Core>().unwrap().mem.vram;
+        let expect = &test[vram.ptr as usize..][..vram.len as usize];
+
+        // Buffers are resolved from the context
+        assert_eq!(found.memory(Region::Vram).unwrap(), expect);
+
+        // Superfluous bytes are ignored
+        let fit = found.memory_fit(Region::Vram, 0x1000).unwrap();
+        assert!(matches!(fit, std::borrow::Cow::Borrowed(_)));
+        assert_eq!(*fit, expect[..0x1000]);
+
+        // Missing bytes are filled with zeros
+        let fit = found.memory_fit(Region::Vram, expect.len() + 0x10).unwrap();
+        assert_eq!(fit[..expect.len()], *expect);
+        assert!(fit[expect.len()..].iter().all(|&byte| byte == 0));
+
+        // Out of bounds pointers are located
+        let found = Bess {
+            ctx: Vec::new(),
+            end: Footer::new(0),
+            ..setup()
+        };
+        let found = found.memory(Region::Wram).unwrap_err();
+        assert!(matches!(found.inner(), Error::Bounds { .. }));
+        assert_eq!(
+            found.context().unwrap().to_string(),
+            "offset 0xc6 in `CORE.mem.wram`"
+        );
+    }
+
+    #[test]
+    fn builder_memory_works() {
+        let test = Bess::builder()
+            .core(core())
+            .vram([0x11; 0x2000])
+            .wram(&[0x22; 0x2000][..])
+            .build(vec![0xaa; CTX])
+            .unwrap();
+        let found = Bess::try_from(&test.to_bytes()[..]).unwrap();
+
+        // Buffers are placed after the context, in order
+        let mem = &found.get::<Core>().unwrap().mem;
+        assert_eq!(
+            mem.wram,
+            Pointer {
+                len: 0x2000,
+                ptr: 0x611f
+            }
+        );
+        assert_eq!(
+            mem.vram,
+            Pointer {
+                len: 0x2000,
+                ptr: 0x811f
+            }
+        );
+        assert_eq!(found.end.start, 0xa11f);
+        assert_eq!(found.memory(Region::Vram).unwrap(), [0x11; 0x2000]);
+        assert_eq!(found.memory(Region::Wram).unwrap(), [0x22; 0x2000]);
+
+        // Other pointers are left untouched
+        assert_eq!(mem.oam, core().mem.oam);
+    }
+
+    #[test]
+    fn lenient_works() {
+        let mut test = bytes();
+        // Follow `CORE` with an unknown block
+        test.splice(CTX + 254..CTX + 254, *b"XBAR\x02\0\0\0\xff\xff");
+        // Give the DMG too large VRAM and too small OAM
+        test[CTX + 207] = 0x40;
+        test[CTX + 222] = 0x50;
+
+        // Strict decoding rejects fallbacks
+        let err = from_bytes_with(&test, Mode::Strict, &Registry::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "buffer size does not match model: `vram` (0x4000 bytes) \
+             (at offset 0x61ed in `CORE.mem.vram`)"
+        );
+        let mut known = test.clone();
+        known[CTX + 207] = 0x20;
+        known[CTX + 222] = 0xa0;
+        let err = from_bytes_with(&known, Mode::Strict, &Registry::new()).unwrap_err();
+        assert!(matches!(err.inner(), Error::Unknown(_)));
+
+        // Lenient decoding applies and reports fallbacks
+        let (found, warn) = from_bytes_with(&test, Mode::Lenient, &Registry::new()).unwrap();
+        assert_eq!(
+            warn.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "warning: ignored extra buffer data: `vram` (0x2000 bytes)",
+                "warning: zero-filled short buffer: `oam` (0x50 bytes)",
+                "warning: unknown block: `XBAR`",
+            ]
+        );
+        assert_eq!(found.blocks().len(), 5);
+        assert_eq!(found.blx[3].get::<Raw>().unwrap().bytes(), [0xff, 0xff]);
+        assert_eq!(found.memory(Region::Vram).unwrap(), &test[0x2000..0x4000]);
+        assert_eq!(found.memory(Region::Oam).unwrap().len(), 0xa0);
+        assert_eq!(found.to_bytes(), test);
+
+        // Borrowed and streaming decoding agree
+        let reg = Registry::new();
+        let (bess, found) = BessRef::new(&test)
+            .unwrap()
+            .to_bess_with(Mode::Lenient, &reg)
+            .unwrap();
+        assert_eq!(found.len(), warn.len());
+        assert_eq!(bess.memory(Region::Oam).unwrap().len(), 0xa0);
+        let (read, found) = Reader::with(std::io::Cursor::new(&test), Mode::Lenient, &reg).unwrap();
+        assert_eq!(found.len(), warn.len());
+        let bess = read.into_bess().unwrap();
+        assert_eq!(bess.memory(Region::Oam).unwrap().len(), 0xa0);
+    }
+
+    #[test]
+    fn version_checked() {
+        use crate::block::core::Version;
+
+        assert!(Version { major: 1, minor: 2 } > Version::CURRENT);
+        assert!(Version { major: 1, minor: 2 }.check().is_ok());
+        assert!(Version { major: 2, minor: 0 }.check().is_err());
+
+        // Newer minors are accepted, but reported when lenient
+        let mut test = bytes();
+        test[CTX + 48] = 0x02;
+        let found = from_bytes(&test).unwrap();
+        assert_eq!(found.get::<Core>().unwrap().version.minor, 2);
+        let (_, warn) = from_bytes_with(&test, Mode::Lenient, &Registry::new()).unwrap();
+        assert_eq!(warn[0].to_string(), "warning: newer minor version: 2");
+
+        // Foreign majors are always rejected
+        let mut test = bytes();
+        test[CTX + 46] = 0x02;
+        let found = from_bytes_with(&test, Mode::Lenient, &Registry::new()).unwrap_err();
+        assert!(matches!(
+            found.inner(),
+            Error::Version(Version { major: 2, minor: 1 })
+        ));
+        assert_eq!(
+            found.context().unwrap().to_string(),
+            "offset 0x614d in `CORE.version`"
+        );
+    }
 }
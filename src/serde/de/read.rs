@@ -0,0 +1,130 @@
+//! Streaming BESS from seekable readers.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::mem;
+
+use super::{Mode, Registry};
+use crate::block::core::Pointer;
+use crate::{Bess, Block, Data, Diagnostic, Error, Footer, Result};
+
+/// A reader which loads only the BESS trailer of a file.
+///
+/// Upon construction, only the footer and the blocks are read. The context, as
+/// well as any buffers it contains, are instead read on demand.
+#[derive(Debug)]
+pub struct Reader<R> {
+    inner: R,
+    blx: Vec<Block>,
+    end: Footer,
+}
+
+impl<R: Read + Seek> Reader<R> {
+    /// Constructs a new `Reader`, decoding the blocks from the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the input cannot be read, or is invalid.
+    pub fn new(inner: R) -> Result<Self> {
+        Self::with(inner, Mode::Strict, &Registry::new()).map(|(this, _)| this)
+    }
+
+    /// Constructs a new `Reader`, decoding the blocks from the input according
+    /// to the specified mode and registry, returning any warnings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the input cannot be read, or is invalid.
+    pub fn with(mut inner: R, mode: Mode, reg: &Registry) -> Result<(Self, Vec<Diagnostic>)> {
+        // Decode the footer
+        let (ftx, end) = Footer::read_from(&mut inner)?;
+        // Decode the blocks
+        let mut out = Vec::new();
+        let blx = {
+            let mut buf = vec![0; ftx - end.start as usize];
+            inner.seek(SeekFrom::Start(end.start.into()))?;
+            inner.read_exact(&mut buf)?;
+            Block::decode_with(&buf, end.start as usize, mode, reg, &mut out)?
+        };
+
+        Ok((Self { inner, blx, end }, out))
+    }
+
+    /// Gets the decoded blocks.
+    #[must_use]
+    pub fn blocks(&self) -> &[Block] {
+        &self.blx
+    }
+
+    /// Gets the body of the first block of type `T`.
+    #[must_use]
+    pub fn get<T: Data>(&self) -> Option<&T> {
+        self.blx.iter().find_map(Block::get)
+    }
+
+    /// Reads the buffer pointed to within the context.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pointer is out of bounds of the context, or the
+    /// input cannot be read.
+    pub fn read(&mut self, ptr: Pointer) -> Result<Vec<u8>> {
+        // Check the bounds
+        let range = ptr.range(self.end.start as usize)?;
+        // Read the buffer
+        let mut buf = vec![0; range.len()];
+        self.inner.seek(SeekFrom::Start(ptr.ptr.into()))?;
+        self.inner.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Reads the entire context.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input cannot be read.
+    pub fn read_ctx(&mut self) -> Result<Vec<u8>> {
+        self.read(Pointer {
+            len: self.end.start,
+            ptr: 0,
+        })
+    }
+
+    /// Converts into a [`Bess`], reading the entire context.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input cannot be read.
+    pub fn into_bess(mut self) -> Result<Bess> {
+        let ctx = self.read_ctx()?;
+        let Self { blx, end, .. } = self;
+        Ok(Bess::from_parts(ctx, blx, end))
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwraps this `Reader`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl Footer {
+    /// Reads the footer from the end of a seekable reader, along with its
+    /// absolute offset.
+    pub(crate) fn read_from<R: Read + Seek>(mut inner: R) -> Result<(usize, Self)> {
+        // Locate the footer
+        let len = inner.seek(SeekFrom::End(0))?;
+        let ftx = usize::try_from(len)
+            .map_err(|_| Error::TooLarge)?
+            .checked_sub(mem::size_of::<Footer>())
+            .ok_or(Error::TooShort)?;
+        // Decode the footer
+        let mut buf = [0; mem::size_of::<Footer>()];
+        inner.seek(SeekFrom::Start(ftx as u64))?;
+        inner.read_exact(&mut buf)?;
+        Footer::decode_at(&buf, ftx).map(|end| (ftx, end))
+    }
+}
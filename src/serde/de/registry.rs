@@ -0,0 +1,139 @@
+//! Registry of decodable block types.
+
+use std::any::TypeId;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+
+use super::Deserializer;
+use crate::block::core::Core;
+use crate::block::end::End;
+use crate::block::huc3::Huc3;
+use crate::block::info::Info;
+use crate::block::mbc::Mbc;
+use crate::block::mbc7::Mbc7;
+use crate::block::name::Name;
+use crate::block::rtc::Rtc;
+use crate::block::sgb::Sgb;
+use crate::block::tpp1::Tpp1;
+use crate::block::xoam::Xoam;
+use crate::block::{Block, Ident};
+use crate::{Error, Result, Typed};
+
+/// Registry of decodable block types.
+///
+/// Built-in block types are always known. User-defined block types may be
+/// registered, such that blocks with their identifier are decoded as that type
+/// rather than being preserved as [`Raw`](crate::block::raw::Raw) blocks. Block
+/// bodies are decoded and encoded using the type's serde implementations.
+///
+/// A registry is passed explicitly to the decoding functions which accept one,
+/// so that separate registries may be used independently.
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    /// User-defined block types, keyed by identifier.
+    map: BTreeMap<Ident, Kind>,
+}
+
+impl Registry {
+    /// Constructs a new `Registry` of only the built-in block types.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a user-defined block type.
+    ///
+    /// Registering the same type more than once has no effect.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the identifier is already in use by a different
+    /// type.
+    pub fn register<T>(&mut self) -> Result<()>
+    where
+        T: Typed + DeserializeOwned + 'static,
+    {
+        let ident = T::IDENT;
+        let kind = Kind::of::<T>();
+        // Built-in types cannot be replaced
+        if builtin(ident).is_some() {
+            return Err(Error::Registered(ident));
+        }
+        // Insert into the registry
+        match self.map.entry(ident) {
+            Entry::Occupied(entry) if entry.get().ty != kind.ty => Err(Error::Registered(ident)),
+            Entry::Occupied(_) => Ok(()),
+            Entry::Vacant(entry) => {
+                entry.insert(kind);
+                Ok(())
+            }
+        }
+    }
+
+    /// Checks if an identifier belongs to a known block type.
+    ///
+    /// Known block types include both built-in and registered types.
+    #[must_use]
+    pub fn is_known(&self, ident: Ident) -> bool {
+        self.lookup(ident).is_some()
+    }
+
+    /// Checks if a block's body is of the known block type for its
+    /// identifier.
+    pub(crate) fn holds(&self, blk: &Block) -> bool {
+        self.lookup(*blk.ident())
+            .is_some_and(|kind| kind.ty == blk.body().as_any().type_id())
+    }
+
+    /// Looks up the known block type for an identifier.
+    pub(crate) fn lookup(&self, ident: Ident) -> Option<Kind> {
+        builtin(ident).or_else(|| self.map.get(&ident).copied())
+    }
+}
+
+/// Looks up the built-in block type for an identifier.
+fn builtin(ident: Ident) -> Option<Kind> {
+    Some(match ident {
+        id if id == Name::IDENT => Kind::of::<Name>(),
+        id if id == Info::IDENT => Kind::of::<Info>(),
+        id if id == Core::IDENT => Kind::of::<Core>(),
+        id if id == End::IDENT => Kind::of::<End>(),
+        id if id == Xoam::IDENT => Kind::of::<Xoam>(),
+        id if id == Mbc::IDENT => Kind::of::<Mbc>(),
+        id if id == Rtc::IDENT => Kind::of::<Rtc>(),
+        id if id == Huc3::IDENT => Kind::of::<Huc3>(),
+        id if id == Tpp1::IDENT => Kind::of::<Tpp1>(),
+        id if id == Mbc7::IDENT => Kind::of::<Mbc7>(),
+        id if id == Sgb::IDENT => Kind::of::<Sgb>(),
+        _ => return None,
+    })
+}
+
+/// Known block type.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Kind {
+    /// Type of the block's body.
+    ty: TypeId,
+    /// Decodes a block body of this type.
+    decode: fn(&mut Deserializer) -> Result<Block>,
+}
+
+impl Kind {
+    /// Constructs the `Kind` for a block type.
+    fn of<T>() -> Self
+    where
+        T: Typed + DeserializeOwned + 'static,
+    {
+        Self {
+            ty: TypeId::of::<T>(),
+            decode: |de| T::deserialize(de).map(Block::from),
+        }
+    }
+
+    /// Decodes a block body of this kind.
+    pub(super) fn decode(&self, de: &mut Deserializer) -> Result<Block> {
+        (self.decode)(de)
+    }
+}
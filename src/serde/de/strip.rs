@@ -0,0 +1,32 @@
+//! Stripping BESS trailers from native save states.
+
+use std::fs::File;
+
+use super::BessRef;
+use crate::{Footer, Result};
+
+/// Strips the BESS trailer from bytes, returning the native save state.
+///
+/// Only the footer is validated; the blocks are not decoded.
+///
+/// # Errors
+///
+/// Returns an error when the footer is invalid.
+pub fn strip(bytes: &[u8]) -> Result<&[u8]> {
+    BessRef::new(bytes).map(|bess| bess.ctx())
+}
+
+/// Strips the BESS trailer from a file in place, leaving only the native save
+/// state.
+///
+/// Only the footer is validated; the blocks are not decoded.
+///
+/// # Errors
+///
+/// Returns an error when the footer is invalid, or the file cannot be read or
+/// truncated.
+pub fn strip_file(mut file: &File) -> Result<()> {
+    let (_, end) = Footer::read_from(&mut file)?;
+    file.set_len(end.start.into())?;
+    Ok(())
+}
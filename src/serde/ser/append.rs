@@ -0,0 +1,120 @@
+//! Appending and replacing BESS trailers of native save states.
+
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+
+use serde::ser;
+
+use super::Serializer;
+use crate::block::core::Core;
+use crate::block::sgb::Sgb;
+use crate::build::Builder;
+use crate::de::BessRef;
+use crate::{Block, Error, Footer, Result};
+
+impl Builder {
+    /// Appends a BESS trailer to the end of a native save state, returning
+    /// the number of bytes written.
+    ///
+    /// The existing contents are left untouched and become the context, so
+    /// pointers within the `CORE` block may refer to buffers already present
+    /// in the native save state. Any memory buffers are written before the
+    /// blocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blocks are invalid, any pointer lies beyond the
+    /// native save state, or writing fails.
+    pub fn append_to<W: Write + Seek>(self, mut file: W) -> Result<usize> {
+        let len = file.seek(SeekFrom::End(0))?;
+        let start = u32::try_from(len).map_err(|_| Error::TooLarge)?;
+        self.write_trailer(file, start)
+    }
+
+    /// Writes a BESS trailer following `start` bytes of native save state,
+    /// returning the number of bytes written.
+    ///
+    /// This is useful for writers which cannot seek, such as when streaming
+    /// the native save state immediately before its trailer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blocks are invalid, any pointer lies beyond the
+    /// native save state, or writing fails.
+    pub fn write_trailer(self, writer: impl Write, start: u32) -> Result<usize> {
+        self.trailer(start)?.write(writer)
+    }
+
+    /// Replaces the BESS trailer of a save state in memory, returning the
+    /// number of bytes written.
+    ///
+    /// The native save state preceding the existing trailer is left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing footer or the new blocks are invalid,
+    /// or any pointer lies beyond the native save state.
+    pub fn replace(self, buf: &mut Vec<u8>) -> Result<usize> {
+        let start = BessRef::new(buf)?.ctx().len();
+        let start = u32::try_from(start).map_err(|_| Error::TooLarge)?;
+        // Build the trailer before touching the buffer
+        let trailer = self.trailer(start)?;
+        buf.truncate(start as usize);
+        trailer.write(buf)
+    }
+
+    /// Replaces the BESS trailer of a save state file in place, returning the
+    /// number of bytes written.
+    ///
+    /// The native save state preceding the existing trailer is left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing footer or the new blocks are invalid,
+    /// any pointer lies beyond the native save state, or the file cannot be
+    /// read or written.
+    pub fn replace_file(self, mut file: &File) -> Result<usize> {
+        let (_, end) = Footer::read_from(&mut file)?;
+        // Build the trailer before touching the file
+        let trailer = self.trailer(end.start)?;
+        file.set_len(end.start.into())?;
+        file.seek(SeekFrom::Start(end.start.into()))?;
+        trailer.write(BufWriter::new(file))
+    }
+
+    /// Builds a trailer following `start` bytes of native save state.
+    fn trailer(self, start: u32) -> Result<Trailer> {
+        // Build blocks
+        let (mem, blx) = self.parts(start as usize)?;
+        let start = u32::try_from(start as usize + mem.len()).map_err(|_| Error::TooLarge)?;
+        // Check the locations
+        if let Some(core) = blx.iter().find_map(Block::get::<Core>) {
+            core.mem.check_len(start as usize)?;
+        }
+        for sgb in blx.iter().filter_map(Block::get::<Sgb>) {
+            sgb.check_len(start as usize)?;
+        }
+        Ok(Trailer { mem, blx, start })
+    }
+}
+
+/// Trailer appended to a native save state.
+struct Trailer {
+    /// Memory buffers preceding the blocks.
+    mem: Vec<u8>,
+    /// Blocks specifying contents.
+    blx: Vec<Block>,
+    /// Start of the blocks.
+    start: u32,
+}
+
+impl Trailer {
+    /// Serializes into a writer, returning the number of bytes written.
+    fn write(self, writer: impl Write) -> Result<usize> {
+        let mut ser = Serializer::new(writer);
+        ser::Serializer::serialize_bytes(&mut ser, &self.mem)?;
+        ser.trailer(&self.blx, Footer::new(self.start))?;
+        ser.output.flush()?;
+        Ok(ser.count)
+    }
+}
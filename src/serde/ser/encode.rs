@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use crate::{Error, Result};
+use crate::Result;
 
 pub trait Encode {
     fn encode(&self, output: impl Write) -> Result<usize>;
@@ -8,7 +8,7 @@ pub trait Encode {
 
 impl Encode for bool {
     fn encode(&self, output: impl Write) -> Result<usize> {
-        u8::from(*self).encode(output).map_err(Error::from)
+        u8::from(*self).encode(output)
     }
 }
 
@@ -16,7 +16,9 @@ macro_rules! add_impl {
     ($($t:ty)*) => ($(
         impl Encode for $t {
             fn encode(&self, mut output: impl Write) -> Result<usize> {
-                output.write(&self.to_le_bytes()).map_err(Error::from)
+                let buf = self.to_le_bytes();
+                output.write_all(&buf)?;
+                Ok(buf.len())
             }
         }
     )*)
@@ -26,12 +28,13 @@ add_impl! { usize u8 u16 u32 u64 u128 isize i8 i16 i32 i64 i128 f32 f64 }
 
 impl Encode for &str {
     fn encode(&self, mut output: impl Write) -> Result<usize> {
-        output.write(self.as_bytes()).map_err(Error::from)
+        self.as_bytes().encode(output)
     }
 }
 
 impl Encode for &[u8] {
     fn encode(&self, mut output: impl Write) -> Result<usize> {
-        output.write(self).map_err(Error::from)
+        output.write_all(self)?;
+        Ok(self.len())
     }
 }
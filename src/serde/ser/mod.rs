@@ -1,39 +1,132 @@
 //! Serializing Rust structures into BESS.
 
+use std::io::Write;
 use std::mem;
 
 use serde::{ser, Serialize};
 
-use crate::{Bess, Error, Result};
+use crate::{Bess, Block, Error, Footer, Result};
 
+mod append;
 mod encode;
 
 use self::encode::Encode;
 
 impl Bess {
     /// Serializes `self` as a byte vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if serialization fails. See [`Bess::try_to_bytes`] for a
+    /// fallible alternative.
     #[inline]
     #[must_use]
     pub fn to_bytes(self) -> Vec<u8> {
         self::to_bytes(&self)
     }
+
+    /// Attempts to serialize `self` as a byte vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any block contains a type that cannot be serialized.
+    #[inline]
+    pub fn try_to_bytes(&self) -> Result<Vec<u8>> {
+        self::try_to_bytes(self)
+    }
+
+    /// Serializes `self` into a writer, returning the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any block contains a type that cannot be serialized,
+    /// or if writing fails.
+    #[inline]
+    pub fn write_to(&self, writer: impl Write) -> Result<usize> {
+        self::to_writer(writer, self)
+    }
 }
 
 /// Serializes the given `Bess` structure as a byte vector.
+///
+/// # Panics
+///
+/// Panics if serialization fails. See [`try_to_bytes`] for a fallible
+/// alternative.
 #[must_use]
 pub fn to_bytes(bess: &Bess) -> Vec<u8> {
-    let mut ser = Serializer::default();
-    bess.serialize(&mut ser).unwrap();
-    ser.output
+    try_to_bytes(bess).unwrap()
+}
+
+/// Attempts to serialize the given `Bess` structure as a byte vector.
+///
+/// # Errors
+///
+/// Returns an error if any block contains a type that cannot be serialized.
+pub fn try_to_bytes(bess: &Bess) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    to_writer(&mut buf, bess)?;
+    Ok(buf)
+}
+
+/// Serializes the given `Bess` structure into a writer, returning the number
+/// of bytes written.
+///
+/// The footer's start is checked against the number of bytes written before
+/// the blocks.
+///
+/// # Errors
+///
+/// Returns an error if any block contains a type that cannot be serialized,
+/// or if writing fails.
+pub fn to_writer<W: Write>(writer: W, bess: &Bess) -> Result<usize> {
+    let mut ser = Serializer::new(writer);
+    // Serialize the context
+    ser::Serializer::serialize_bytes(&mut ser, &bess.ctx)?;
+    // Check the footer
+    if ser.count != bess.end.start as usize {
+        return Err(Error::Start(bess.end.start).at(ser.count));
+    }
+    // Serialize the trailer
+    ser.trailer(&bess.blx, bess.end)?;
+    Ok(ser.count)
 }
 
 /// A structure for serializing Rust structures into BESS.
-#[derive(Debug, Default)]
-struct Serializer {
-    output: Vec<u8>,
+#[derive(Debug)]
+struct Serializer<W> {
+    output: W,
+    /// Number of bytes written.
+    count: usize,
 }
 
-impl ser::Serializer for &mut Serializer {
+impl<W: Write> Serializer<W> {
+    /// Constructs a `Serializer` from a writer.
+    #[must_use]
+    pub fn new(output: W) -> Self {
+        Self { output, count: 0 }
+    }
+
+    /// Serializes the blocks followed by the footer.
+    fn trailer(&mut self, blx: &[Block], end: Footer) -> Result<()> {
+        blx.serialize(&mut *self)?;
+        end.serialize(&mut *self)
+    }
+
+    /// Encodes a value into the output.
+    fn emit(&mut self, value: &impl Encode) -> Result<()> {
+        self.count += value.encode(&mut self.output)?;
+        Ok(())
+    }
+}
+
+impl Default for Serializer<Vec<u8>> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<W: Write> ser::Serializer for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -46,47 +139,47 @@ impl ser::Serializer for &mut Serializer {
     type SerializeStructVariant = Self;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
-        v.encode(&mut self.output).map(|_| ())
+        self.emit(&v)
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
-        v.encode(&mut self.output).map(|_| ())
+        self.emit(&v)
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
-        v.encode(&mut self.output).map(|_| ())
+        self.emit(&v)
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
-        v.encode(&mut self.output).map(|_| ())
+        self.emit(&v)
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
-        v.encode(&mut self.output).map(|_| ())
+        self.emit(&v)
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
-        v.encode(&mut self.output).map(|_| ())
+        self.emit(&v)
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
-        v.encode(&mut self.output).map(|_| ())
+        self.emit(&v)
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
-        v.encode(&mut self.output).map(|_| ())
+        self.emit(&v)
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
-        v.encode(&mut self.output).map(|_| ())
+        self.emit(&v)
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
-        v.encode(&mut self.output).map(|_| ())
+        self.emit(&v)
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
-        v.encode(&mut self.output).map(|_| ())
+        self.emit(&v)
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok> {
@@ -98,16 +191,16 @@ impl ser::Serializer for &mut Serializer {
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
-        v.encode(&mut self.output).map(|_| ())
+        self.emit(&v)
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
         Err(Error::Unsupported)
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
         Err(Error::Unsupported)
     }
@@ -135,14 +228,14 @@ impl ser::Serializer for &mut Serializer {
         }
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(self, name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
         value.serialize(self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         self,
         name: &'static str,
         variant_index: u32,
@@ -150,7 +243,7 @@ impl ser::Serializer for &mut Serializer {
         value: &T,
     ) -> Result<Self::Ok>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
         value.serialize(self)
     }
@@ -200,13 +293,13 @@ impl ser::Serializer for &mut Serializer {
     }
 }
 
-impl ser::SerializeSeq for &mut Serializer {
+impl<W: Write> ser::SerializeSeq for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(&mut **self)
     }
@@ -216,13 +309,13 @@ impl ser::SerializeSeq for &mut Serializer {
     }
 }
 
-impl ser::SerializeTuple for &mut Serializer {
+impl<W: Write> ser::SerializeTuple for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(&mut **self)
     }
@@ -232,13 +325,13 @@ impl ser::SerializeTuple for &mut Serializer {
     }
 }
 
-impl ser::SerializeTupleStruct for &mut Serializer {
+impl<W: Write> ser::SerializeTupleStruct for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(&mut **self)
     }
@@ -248,13 +341,13 @@ impl ser::SerializeTupleStruct for &mut Serializer {
     }
 }
 
-impl ser::SerializeTupleVariant for &mut Serializer {
+impl<W: Write> ser::SerializeTupleVariant for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(&mut **self)
     }
@@ -264,20 +357,20 @@ impl ser::SerializeTupleVariant for &mut Serializer {
     }
 }
 
-impl ser::SerializeMap for &mut Serializer {
+impl<W: Write> ser::SerializeMap for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<()>
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         Ok(())
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(&mut **self)
     }
@@ -287,13 +380,13 @@ impl ser::SerializeMap for &mut Serializer {
     }
 }
 
-impl ser::SerializeStruct for &mut Serializer {
+impl<W: Write> ser::SerializeStruct for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(&mut **self)
     }
@@ -303,13 +396,13 @@ impl ser::SerializeStruct for &mut Serializer {
     }
 }
 
-impl ser::SerializeStructVariant for &mut Serializer {
+impl<W: Write> ser::SerializeStructVariant for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(&mut **self)
     }
@@ -322,7 +415,7 @@ impl ser::SerializeStructVariant for &mut Serializer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::build::tests::{setup, BYTES};
+    use crate::build::tests::{bytes, core, setup};
 
     #[test]
     fn int_serialize_works() {
@@ -373,8 +466,92 @@ mod tests {
     fn builder_serialize_works() {
         let test = setup();
         let found = test.to_bytes();
-        let expect = BYTES;
+        let expect = bytes();
+
+        assert_eq!(found, expect);
+    }
+
+    #[test]
+    fn writer_serialize_works() {
+        let test = setup();
+        let mut found = Vec::new();
+        let len = test.write_to(&mut found).unwrap();
+        let expect = bytes();
+
+        assert_eq!(len, expect.len());
+        assert_eq!(found, expect);
+    }
+
+    #[test]
+    fn footer_serialize_checked() {
+        let mut test = setup();
+        test.end.start += 1;
+
+        let found = test.try_to_bytes().unwrap_err();
+        assert!(matches!(found.inner(), Error::Start(_)));
+        assert_eq!(found.context().unwrap().offset, test.ctx.len());
+    }
+
+    #[test]
+    fn append_serialize_works() {
+        let native: Vec<u8> = (0..=u8::MAX).cycle().take(0x10000).collect();
+
+        // Trailer is appended in place
+        let mut file = std::io::Cursor::new(native.clone());
+        let len = Bess::builder()
+            .name("bess")
+            .core(core())
+            .append_to(&mut file)
+            .unwrap();
+        let found = file.into_inner();
+        assert_eq!(found.len(), native.len() + len);
+        assert_eq!(found[..native.len()], native);
+
+        // Pointers refer to the native save state
+        let bess = Bess::try_from(&found[..]).unwrap();
+        assert_eq!(bess.ctx, native);
+        assert_eq!(bess.end.start as usize, native.len());
+        let vram = bess.get::<crate::block::core::Core>().unwrap().mem.vram;
+        assert_eq!(vram.get(&bess.ctx).unwrap(), &native[0x2000..0x4000]);
+
+        // Pointers beyond the native save state are rejected
+        let found = Bess::builder()
+            .core(core())
+            .write_trailer(std::io::sink(), 0x6000)
+            .unwrap_err();
+        assert!(matches!(found, Error::Bounds { .. }));
+    }
+
+    #[test]
+    fn replace_serialize_works() {
+        let test = include_bytes!("../../../data/saves/tetris.sav");
+        let native = &test[..0xc490];
+        let builder = || {
+            let core = crate::de::BessRef::new(test).unwrap().core().unwrap();
+            Bess::builder().name("bess").core(core)
+        };
 
+        // Replace in memory
+        let mut found = test.to_vec();
+        let len = builder().replace(&mut found).unwrap();
+        assert_eq!(found.len(), native.len() + len);
+        assert_eq!(&found[..native.len()], native);
+        let bess = Bess::try_from(&found[..]).unwrap();
+        assert_eq!(bess.blx.len(), 3);
+        assert_eq!(bess.end.start as usize, native.len());
+
+        // Replace within a file
+        let path = std::env::temp_dir().join(format!("bess-{}.sav", std::process::id()));
+        std::fs::write(&path, test).unwrap();
+        let file = std::fs::File::options()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        builder().replace_file(&file).unwrap();
+        drop(file);
+        let expect = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
         assert_eq!(found, expect);
     }
 }
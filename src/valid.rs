@@ -0,0 +1,346 @@
+//! Checking BESS against the specification.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::mem;
+
+use crate::block::core::{Core, Model, Region, Version};
+use crate::block::end::End;
+use crate::block::info::Info;
+use crate::block::name::Name;
+use crate::block::{Block, Header, Ident};
+#[cfg(feature = "serde")]
+use crate::de::Registry;
+use crate::{Bess, Footer, Typed};
+
+impl Bess {
+    /// Checks that `self` follows the specification.
+    ///
+    /// Returns every diagnostic found, which is empty when `self` is valid.
+    #[must_use]
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        #[cfg(feature = "serde")]
+        let known = |blk: &Block| Registry::new().holds(blk);
+        #[cfg(not(feature = "serde"))]
+        let known = |blk: &Block| blk.get::<crate::block::raw::Raw>().is_none();
+        self.diagnose(&known)
+    }
+
+    /// Checks that `self` follows the specification, with the block types
+    /// known to `reg`.
+    ///
+    /// Blocks are only reported as unknown if they are not of the type known
+    /// for their identifier.
+    #[cfg(feature = "serde")]
+    #[must_use]
+    pub fn validate_with(&self, reg: &Registry) -> Vec<Diagnostic> {
+        self.diagnose(&|blk| reg.holds(blk))
+    }
+
+    /// Collects the diagnostics for `self`, using `known` to check whether
+    /// each block is of a known type.
+    fn diagnose(&self, known: &dyn Fn(&Block) -> bool) -> Vec<Diagnostic> {
+        let mut out = Vec::new();
+        self.check_order(&mut out);
+        self.check_blocks(known, &mut out);
+        if let Some(core) = self.get::<Core>() {
+            self.check_core(core, &mut out);
+        }
+        out
+    }
+
+    /// Checks the order of the `NAME`, `INFO`, `CORE` and `END` blocks.
+    fn check_order(&self, out: &mut Vec<Diagnostic>) {
+        let idents: Vec<_> = self.blx.iter().map(|blk| *blk.ident()).collect();
+        let first = |ident| idents.iter().position(|&id| id == ident);
+        // Only `NAME` and `INFO` may precede `CORE`, in that order
+        let lead = idents
+            .iter()
+            .take_while(|&&id| id == Name::IDENT || id == Info::IDENT)
+            .count();
+        if lead > 1 && idents[..lead] != [Name::IDENT, Info::IDENT] {
+            out.push(Diagnostic::warning(Issue::Order(Name::IDENT)));
+        }
+        for ident in [Name::IDENT, Info::IDENT] {
+            if first(ident).is_some_and(|idx| idx >= lead) {
+                out.push(Diagnostic::warning(Issue::Order(ident)));
+            }
+        }
+        match first(Core::IDENT) {
+            None => out.push(Diagnostic::error(Issue::Required(Core::IDENT))),
+            Some(idx) if idx != lead => out.push(Diagnostic::error(Issue::Order(Core::IDENT))),
+            Some(_) => (),
+        }
+        // `END` must be last
+        match first(End::IDENT) {
+            None => out.push(Diagnostic::error(Issue::Required(End::IDENT))),
+            Some(_) if idents.last() != Some(&End::IDENT) => {
+                out.push(Diagnostic::error(Issue::Order(End::IDENT)));
+            }
+            Some(_) => (),
+        }
+    }
+
+    /// Checks each block's identifier and length.
+    fn check_blocks(&self, known: &dyn Fn(&Block) -> bool, out: &mut Vec<Diagnostic>) {
+        let mut seen = BTreeMap::new();
+        for blk in &self.blx {
+            let ident = *blk.ident();
+            // Duplicate blocks
+            let count = seen.entry(ident).or_insert(0);
+            *count += 1;
+            if *count == 2 {
+                out.push(Diagnostic::warning(Issue::Duplicate(ident)));
+            }
+            // Unknown blocks
+            if !known(blk) {
+                out.push(Diagnostic::info(Issue::Unknown(ident)));
+            }
+            // Mismatched lengths
+            if blk.len() != blk.body().len() {
+                out.push(Diagnostic::error(Issue::Length {
+                    ident,
+                    len: blk.len(),
+                }));
+            }
+        }
+    }
+
+    /// Checks the contents of the `CORE` block.
+    fn check_core(&self, core: &Core, out: &mut Vec<Diagnostic>) {
+        // Versions
+        if core.version.check().is_err() {
+            out.push(Diagnostic::error(Issue::Version(core.version)));
+        }
+        // Pointers
+        let start = self.end.start as usize;
+        let len = self
+            .blx
+            .iter()
+            .fold(start + mem::size_of::<Footer>(), |len, blk| {
+                len + mem::size_of::<Header>() + blk.len() as usize
+            });
+        for region in Region::ALL {
+            let ptr = core.mem.get(region);
+            match ptr.range(len) {
+                Err(_) => out.push(Diagnostic::error(Issue::Bounds(region))),
+                Ok(range) if range.end > start => {
+                    out.push(Diagnostic::error(Issue::Overlap(region)));
+                }
+                Ok(_) => (),
+            }
+        }
+        // Models
+        if core.model.check().is_err() {
+            out.push(Diagnostic::error(Issue::Model(core.model)));
+        }
+        // Palettes
+        if !core.model.is_cgb() {
+            for region in [Region::Bgp, Region::Obj] {
+                if core.mem.get(region).len != 0 {
+                    out.push(Diagnostic::error(Issue::Palette(region)));
+                }
+            }
+        }
+    }
+}
+
+/// A problem found while validating.
+#[derive(Debug)]
+pub struct Diagnostic {
+    /// How severe the problem is.
+    pub severity: Severity,
+    /// What the problem is.
+    pub issue: Issue,
+}
+
+impl Diagnostic {
+    /// Constructs a new informational `Diagnostic`.
+    pub(crate) fn info(issue: Issue) -> Self {
+        Self {
+            severity: Severity::Info,
+            issue,
+        }
+    }
+
+    /// Constructs a new warning `Diagnostic`.
+    pub(crate) fn warning(issue: Issue) -> Self {
+        Self {
+            severity: Severity::Warning,
+            issue,
+        }
+    }
+
+    /// Constructs a new error `Diagnostic`.
+    pub(crate) fn error(issue: Issue) -> Self {
+        Self {
+            severity: Severity::Error,
+            issue,
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.issue)
+    }
+}
+
+/// Severity of a diagnostic.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// Allowed by the specification, but may be of interest.
+    Info,
+    /// Discouraged by the specification.
+    Warning,
+    /// Forbidden by the specification.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+/// Problems with respect to the specification.
+#[derive(Debug)]
+pub enum Issue {
+    Bounds(Region),
+    Duplicate(Ident),
+    Extra { region: Region, len: u32 },
+    Length { ident: Ident, len: u32 },
+    Minor(u16),
+    Model(Model),
+    Order(Ident),
+    Overlap(Region),
+    Palette(Region),
+    Required(Ident),
+    Short { region: Region, len: u32 },
+    Unknown(Ident),
+    Version(Version),
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bounds(region) => write!(f, "pointer out of bounds: `{}`", region.name()),
+            Self::Duplicate(ident) => write!(f, "duplicate block: `{ident}`"),
+            Self::Extra { region, len } => write!(
+                f,
+                "ignored extra buffer data: `{}` ({len:#x} bytes)",
+                region.name()
+            ),
+            Self::Length { ident, len } => write!(
+                f,
+                "block length does not match type: `{ident}` ({len:#x} bytes)"
+            ),
+            Self::Minor(minor) => write!(f, "newer minor version: {minor}"),
+            Self::Model(model) => write!(f, "invalid model: `{model}`"),
+            Self::Order(ident) => write!(f, "block is out of order: `{ident}`"),
+            Self::Overlap(region) => write!(f, "pointer overlaps blocks: `{}`", region.name()),
+            Self::Palette(region) => write!(
+                f,
+                "palette on model prior to Game Boy Color: `{}`",
+                region.name()
+            ),
+            Self::Required(ident) => write!(f, "required block is missing: `{ident}`"),
+            Self::Short { region, len } => write!(
+                f,
+                "zero-filled short buffer: `{}` ({len:#x} bytes)",
+                region.name()
+            ),
+            Self::Unknown(ident) => write!(f, "unknown block: `{ident}`"),
+            Self::Version(version) => write!(f, "incompatible version: {version}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::core::Pointer;
+    use crate::block::mbc::Mbc;
+    use crate::build::tests::{core, setup};
+    use crate::Block;
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn save_valid() {
+        let test = include_bytes!("../data/saves/tetris.sav");
+        let found = Bess::try_from(&test[..]).unwrap().validate();
+
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    #[test]
+    fn pointers_validated() {
+        let mut core = core();
+        core.mem.wram = Pointer { len: 0x10, ptr: 0 };
+        core.mem.vram = Pointer {
+            len: 0x10,
+            ptr: 0x18,
+        };
+        core.mem.eram = Pointer {
+            len: 0x10,
+            ptr: 0x1000,
+        };
+        core.mem.oam = Pointer::default();
+        core.mem.hram = Pointer::default();
+        core.mem.bgp = Pointer { len: 0x08, ptr: 0 };
+        core.version.major = 2;
+        let test = Bess {
+            ctx: vec![0; 0x20],
+            blx: vec![core.into(), End.into()],
+            end: Footer::new(0x20),
+            fit: BTreeMap::new(),
+        };
+        let found: Vec<_> = test
+            .validate()
+            .into_iter()
+            .map(|diag| diag.to_string())
+            .collect();
+
+        assert_eq!(
+            found,
+            [
+                "error: incompatible version: 2.1",
+                "error: pointer overlaps blocks: `vram`",
+                "error: pointer out of bounds: `eram`",
+                "error: palette on model prior to Game Boy Color: `bgp`",
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks_validated() {
+        let mut test = setup();
+        // Move `NAME` after `CORE`, and duplicate `MBC `
+        let name = test.blx.remove(0);
+        test.blx.insert(2, name);
+        test.blx.insert(3, Block::from(Mbc::new([]).unwrap()));
+        test.blx.insert(3, Block::from(Mbc::new([]).unwrap()));
+        // Move `END` before the last block
+        let end = test.blx.pop().unwrap();
+        test.blx.insert(test.blx.len() - 1, end);
+        let found: Vec<_> = test
+            .validate()
+            .into_iter()
+            .filter(|diag| !matches!(diag.issue, Issue::Bounds(_)))
+            .map(|diag| diag.to_string())
+            .collect();
+
+        assert_eq!(
+            found,
+            [
+                "warning: block is out of order: `NAME`",
+                "error: block is out of order: `END `",
+                "warning: duplicate block: `MBC `",
+            ]
+        );
+    }
+}